@@ -0,0 +1,94 @@
+//! Hooks to keep an audit log of the issued proofs and the leaf updates.
+//!
+//! Attach the log to the tree with [`SparseMerkleTree::set_audit_log`], then every proof and
+//! update goes through it, including the ones of [`ProofCache`](crate::proof_cache::ProofCache).
+//! The tamper evidence is up to the log, e.g. chain [`Proof::digest`] and the roots into the
+//! previous entry before persisting it.
+
+// --- alloc ---
+use alloc::sync::Arc;
+// --- sparse-merkle-tree ---
+use crate::*;
+
+/// All the hooks are no-op by default, `()` ignores everything.
+///
+/// The hooks take `&self` since [`SparseMerkleTree::proof_of`] does, use a lock or a channel to
+/// record the entries.
+pub trait AuditLog<H, M>: Send + Sync {
+	/// Called after the proof is built, `indices` are as requested.
+	fn on_proof(&self, _indices: &[u32], _proof: &Proof<H, M>) {}
+
+	/// Called after the leaves are replaced or appended.
+	fn on_update(&self, _indices: &[u32], _root_before: &H, _root_after: &H) {}
+}
+impl<H, M> AuditLog<H, M> for () {}
+
+pub type SharedAuditLog<H, M> = Arc<dyn AuditLog<H, M>>;
+
+impl<H, M> SparseMerkleTree<H, M> {
+	/// Report every proof and leaf update of this tree to `log`, replace the previous one if any.
+	pub fn set_audit_log(&mut self, log: SharedAuditLog<H, M>) {
+		self.audit_log = Some(log);
+	}
+
+	pub fn audit_log(&self) -> Option<&SharedAuditLog<H, M>> {
+		self.audit_log.as_ref()
+	}
+
+	pub fn take_audit_log(&mut self) -> Option<SharedAuditLog<H, M>> {
+		self.audit_log.take()
+	}
+
+	pub(crate) fn audit_proof(&self, indices: &[u32], proof: &Proof<H, M>) {
+		if let Some(log) = &self.audit_log {
+			log.on_proof(indices, proof);
+		}
+	}
+}
+
+impl<H, M> SparseMerkleTree<H, M>
+where
+	H: Clone + MaybeDebug + PartialEq,
+	M: Merge<Item = H>,
+{
+	/// The root before an update, only if there is a log to report to.
+	pub(crate) fn audit_root(&self) -> Option<H> {
+		self.audit_log.as_ref().map(|_| self.root())
+	}
+
+	pub(crate) fn audit_update(&self, indices: &[u32], root_before: Option<H>) {
+		if let (Some(log), Some(root_before)) = (&self.audit_log, root_before) {
+			log.on_update(indices, &root_before, &self.root());
+		}
+	}
+}
+
+impl<H, M> Proof<H, M>
+where
+	H: Clone + MaybeDebug + PartialEq,
+	M: MergeCount<Item = H>,
+{
+	/// Fold everything of the proof with `M`, the lengths and the indices with [`MergeCount::count`].
+	///
+	/// ```text
+	/// root | leaves count | (index, leaf)* | siblings count | sibling* | 0 or 1 | leaves count
+	/// ```
+	pub fn digest(&self) -> H {
+		let fold = |acc: H, node: &H| M::merge(&acc, node);
+		let count = |n: usize| M::count(n as _);
+		let mut acc = fold(self.root.clone(), &count(self.leaves_with_index.len()));
+
+		for (i, leaf) in &self.leaves_with_index {
+			acc = fold(acc, &M::count(*i));
+			acc = fold(acc, leaf);
+		}
+
+		acc = fold(acc, &count(self.proof.len()));
+		acc = self.proof.iter().fold(acc, fold);
+
+		match self.non_empty_leaves_count {
+			Some(n) => fold(fold(acc, &count(1)), &M::count(n)),
+			None => fold(acc, &count(0)),
+		}
+	}
+}
@@ -0,0 +1,71 @@
+//! Deterministic worst-case inputs for the benchmarks, e.g. the FRAME ones of a pallet.
+//!
+//! ```ignore
+//! let (tree, proof) = benchmarking::worst_case::<_, Keccak256, _>(MAX_DEPTH, k, |i| {
+//!     Keccak256::hash(i.to_le_bytes())
+//! });
+//!
+//! #[block]
+//! {
+//!     assert!(SparseMerkleTree::verify(proof));
+//! }
+//! ```
+
+// --- alloc ---
+use alloc::vec::Vec;
+// --- sparse-merkle-tree ---
+use crate::*;
+
+/// A full tree of `depth`, every leaf slot is non-empty.
+///
+/// `leaf(i)` produces the leaf at index `i`. Panic if `depth` is larger than [`MAX_DEPTH`].
+pub fn full_tree<H, M, F>(depth: u32, leaf: F) -> SparseMerkleTree<H, M>
+where
+	H: Clone + MaybeDebug + PartialEq,
+	M: Merge<Item = H>,
+	F: FnMut(u32) -> H,
+{
+	assert!(depth <= MAX_DEPTH, "the depth is too large");
+
+	SparseMerkleTree::new((0..1 << depth).map(leaf))
+}
+
+/// The indices of `leaves_count` leaves spread across `depth`, in the descending order.
+///
+/// Take the bit-reversed `0..leaves_count`, every level splits them as evenly as possible. They
+/// share the least path segments, so their proof takes the most siblings and merges.
+///
+/// `depth` is clamped to [`MAX_DEPTH`].
+pub fn worst_case_indices(depth: u32, leaves_count: u32) -> Vec<u32> {
+	let depth = depth.min(MAX_DEPTH);
+	let leaves_count = leaves_count.clamp(1, 1 << depth);
+	let mut indices = (0..leaves_count)
+		// The only leaf of a zero depth tree is at `0`.
+		.map(|i| i.reverse_bits().checked_shr(u32::BITS - depth).unwrap_or(0))
+		.collect::<Vec<_>>();
+
+	indices.sort_unstable_by(|a, b| b.cmp(a));
+
+	indices
+}
+
+/// [`full_tree`] and the proof of [`worst_case_indices`], ready for the verification.
+///
+/// Panic if `depth` is larger than [`MAX_DEPTH`].
+pub fn worst_case<H, M, F>(
+	depth: u32,
+	leaves_count: u32,
+	leaf: F,
+) -> (SparseMerkleTree<H, M>, Proof<H, M>)
+where
+	H: Clone + MaybeDebug + PartialEq,
+	M: Merge<Item = H>,
+	F: FnMut(u32) -> H,
+{
+	assert!(depth <= MAX_DEPTH, "the depth is too large");
+
+	let tree = full_tree(depth, leaf);
+	let proof = tree.proof_of(worst_case_indices(depth, leaves_count));
+
+	(tree, proof)
+}
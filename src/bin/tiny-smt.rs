@@ -0,0 +1,230 @@
+//! Build a tree from a leaves file, print the root and a multiproof as JSON.
+//!
+//! ```text
+//! tiny-smt <LEAVES> [--hasher <HASHER>] [--format <FORMAT>] [--prove 0,1,2 | --verify <PROOF>]
+//! ```
+//!
+//! `LEAVES` is either a JSON array of hex strings, or a CSV file whose first column is the hex
+//! leaf. The leaves must be already hashed.
+//!
+//! `HASHER` is either `keccak256`, or `keccak256-sorted` which sorts each pair before hashing,
+//! e.g. for the OpenZeppelin `MerkleProof`. `FORMAT` is one of `compact`, `compact-be`, `scale`
+//! and `abi`.
+//!
+//! `--prove` prints a single multiproof of all the indices, with the leaves in the descending
+//! order. `--verify` checks a hex encoded proof against the root of `LEAVES`, and exits with `1`
+//! if it's invalid.
+
+// --- std ---
+use std::{env, fs, process};
+// --- crates.io ---
+use serde_json::{json, Value};
+// --- sparse-merkle-tree ---
+use tiny_sparse_merkle_tree::{
+	codec::{self, Abi, Compact, CompactBe, ProofCodec, Scale},
+	dyn_merge::{Dyn, DynMerge, MergeAdapter},
+	foreign::SortedPair,
+	hash::Keccak256,
+	Proof, SparseMerkleTree,
+};
+
+type Hash = [u8; 32];
+type Encoder = fn(&Proof<Hash, Dyn>) -> Vec<u8>;
+type Decoder = fn(&[u8]) -> codec::Result<Proof<Hash, Dyn>>;
+
+const USAGE: &str = "usage: tiny-smt <LEAVES> [--hasher keccak256|keccak256-sorted] [--format compact|compact-be|scale|abi] [--prove 0,1,2 | --verify <PROOF>]";
+
+struct Args {
+	leaves: String,
+	hasher: String,
+	format: String,
+	indices: Vec<u32>,
+	proof: Option<String>,
+}
+impl Args {
+	fn parse() -> Result<Self, String> {
+		let mut args = env::args().skip(1);
+		let mut leaves = None;
+		let mut hasher = "keccak256".into();
+		let mut format = "compact".into();
+		let mut indices = Vec::new();
+		let mut proof = None;
+
+		while let Some(arg) = args.next() {
+			let mut value = || {
+				args.next()
+					.ok_or_else(|| format!("missing value of `{}`", arg))
+			};
+
+			match arg.as_str() {
+				"--hasher" => hasher = value()?,
+				"--format" => format = value()?,
+				"--prove" => {
+					for index in value()?.split(',').filter(|s| !s.is_empty()) {
+						indices.push(
+							index
+								.trim()
+								.parse()
+								.map_err(|_| format!("invalid index `{}`", index))?,
+						);
+					}
+				}
+				"--verify" => proof = Some(value()?),
+				"-h" | "--help" => return Err(USAGE.into()),
+				_ if leaves.is_none() && !arg.starts_with('-') => leaves = Some(arg),
+				_ => return Err(format!("unexpected argument `{}`\n{}", arg, USAGE)),
+			}
+		}
+
+		if !indices.is_empty() && proof.is_some() {
+			return Err(format!("`--prove` conflicts with `--verify`\n{}", USAGE));
+		}
+
+		Ok(Self {
+			leaves: leaves.ok_or(USAGE)?,
+			hasher,
+			format,
+			indices,
+			proof,
+		})
+	}
+}
+
+fn merger_of(hasher: &str) -> Result<Box<dyn DynMerge<Hash>>, String> {
+	match hasher {
+		"keccak256" => Ok(Box::new(MergeAdapter::<Keccak256>::new())),
+		"keccak256-sorted" => Ok(Box::new(MergeAdapter::<SortedPair<Keccak256>>::new())),
+		_ => Err(format!("unsupported hasher `{}`", hasher)),
+	}
+}
+
+fn codec_of(format: &str) -> Result<(Encoder, Decoder), String> {
+	fn codec<C>() -> (Encoder, Decoder)
+	where
+		C: ProofCodec<Hash>,
+	{
+		(C::encode, C::decode)
+	}
+
+	match format {
+		"compact" => Ok(codec::<Compact>()),
+		"compact-be" => Ok(codec::<CompactBe>()),
+		"scale" => Ok(codec::<Scale>()),
+		"abi" => Ok(codec::<Abi>()),
+		_ => Err(format!("unsupported format `{}`", format)),
+	}
+}
+
+fn read_leaves(path: &str) -> Result<Vec<Hash>, String> {
+	let data = fs::read_to_string(path).map_err(|e| format!("failed to read `{}`: {}", path, e))?;
+	let hexes = if path.ends_with(".json") {
+		serde_json::from_str::<Vec<String>>(&data)
+			.map_err(|e| format!("invalid JSON leaves: {}", e))?
+	} else {
+		data.lines()
+			.filter_map(|line| line.split(',').next())
+			.map(|s| s.trim().to_owned())
+			.filter(|s| !s.is_empty())
+			.collect()
+	};
+
+	hexes.iter().map(|hex| decode_hash(hex)).collect()
+}
+
+fn decode_hash(hex: &str) -> Result<Hash, String> {
+	decode_hex(hex)
+		.ok_or_else(|| format!("invalid leaf `{}`, not a hex string", hex))?
+		.try_into()
+		.map_err(|_| format!("invalid leaf `{}`, expect 32 bytes", hex))
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+	let digits = hex.trim_start_matches("0x").as_bytes();
+
+	if digits.len() % 2 != 0 {
+		return None;
+	}
+
+	digits
+		.chunks(2)
+		.map(|pair| {
+			std::str::from_utf8(pair)
+				.ok()
+				.and_then(|pair| u8::from_str_radix(pair, 16).ok())
+		})
+		.collect()
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+	let mut hex = String::with_capacity(2 + bytes.len() * 2);
+
+	hex.push_str("0x");
+	bytes
+		.iter()
+		.for_each(|byte| hex.push_str(&format!("{:02x}", byte)));
+
+	hex
+}
+
+fn run() -> Result<Value, String> {
+	let args = Args::parse()?;
+	let merger = merger_of(&args.hasher)?;
+	let (encode, decode) = codec_of(&args.format)?;
+	let leaves = read_leaves(&args.leaves)?;
+	let smt = SparseMerkleTree::<Hash, Dyn>::new_dyn(leaves, &*merger);
+	let root = smt.root_dyn(&*merger);
+
+	if let Some(proof) = args.proof {
+		let proof = decode_hex(&proof)
+			.ok_or_else(|| format!("invalid proof `{}`, not a hex string", proof))?;
+		let proof = decode(&proof).map_err(|e| format!("invalid proof: {:?}", e))?;
+		let valid = proof.root() == &root && SparseMerkleTree::verify_dyn(proof, &*merger);
+
+		return Ok(json!({
+			"hasher": args.hasher,
+			"format": args.format,
+			"root": encode_hex(&root),
+			"valid": valid,
+		}));
+	}
+
+	let mut indices = args.indices;
+
+	indices.sort_unstable_by(|a, b| b.cmp(a));
+	indices.dedup();
+
+	if let Some(index) = indices
+		.first()
+		.filter(|i| **i >= smt.non_empty_leaves_count())
+	{
+		return Err(format!("index `{}` out of bounds", index));
+	}
+
+	let proof =
+		(!indices.is_empty()).then(|| encode_hex(&encode(&smt.proof_of_dyn(&indices, &*merger))));
+
+	Ok(json!({
+		"hasher": args.hasher,
+		"format": args.format,
+		"leavesCount": smt.non_empty_leaves_count(),
+		"root": encode_hex(&root),
+		"indices": indices,
+		"proof": proof,
+	}))
+}
+
+fn main() {
+	match run() {
+		Ok(output) => {
+			println!("{:#}", output);
+
+			if output["valid"] == false {
+				process::exit(1);
+			}
+		}
+		Err(e) => {
+			eprintln!("{}", e);
+			process::exit(1);
+		}
+	}
+}
@@ -0,0 +1,173 @@
+//! The Solidity [ABI](https://docs.soliditylang.org/en/latest/abi-spec.html) format.
+//!
+//! Equivalent to `abi.encode(root, leavesCount, indices, leaves, proof)` with the types
+//! `(bytes32, uint32, uint32[], bytes32[], bytes32[])`.
+//!
+//! `leavesCount` is `0` unless the proof is against the committed root.
+//!
+//! A hash longer than 32 bytes takes several consecutive words, a shorter one is right-padded with
+//! zeros.
+
+// --- core ---
+use core::marker::PhantomData;
+// --- alloc ---
+use alloc::vec::Vec;
+// --- sparse-merkle-tree ---
+use crate::{codec::*, Proof};
+
+const WORD: usize = 32;
+
+pub struct Abi;
+impl<H> ProofCodec<H> for Abi
+where
+	H: AsMut<[u8]> + AsRef<[u8]> + Default,
+{
+	/// The ABI words are always big-endian.
+	const ENDIANNESS: Endianness = Endianness::Big;
+
+	fn encode<M>(proof: &Proof<H, M>) -> Vec<u8> {
+		let hash_size = hash_words::<H>() * WORD;
+		let leaves_count = proof.leaves_with_index.len();
+		let head_size = hash_size + 4 * WORD;
+		let indices_offset = head_size;
+		let leaves_offset = indices_offset + WORD + leaves_count * WORD;
+		let proof_offset = leaves_offset + WORD + leaves_count * hash_size;
+		let mut data = Vec::with_capacity(proof_offset + WORD + proof.proof.len() * hash_size);
+
+		encode_hash(&proof.root, hash_size, &mut data);
+		encode_uint(
+			proof.non_empty_leaves_count.unwrap_or_default() as _,
+			&mut data,
+		);
+		encode_uint(indices_offset, &mut data);
+		encode_uint(leaves_offset, &mut data);
+		encode_uint(proof_offset, &mut data);
+		encode_uint(leaves_count, &mut data);
+		proof
+			.leaves_with_index
+			.iter()
+			.for_each(|(i, _)| encode_uint(*i as _, &mut data));
+		encode_uint(leaves_count, &mut data);
+		proof
+			.leaves_with_index
+			.iter()
+			.for_each(|(_, leaf)| encode_hash(leaf, hash_size, &mut data));
+		encode_uint(proof.proof.len(), &mut data);
+		proof
+			.proof
+			.iter()
+			.for_each(|sibling| encode_hash(sibling, hash_size, &mut data));
+
+		data
+	}
+
+	fn decode<M>(data: &[u8]) -> Result<Proof<H, M>> {
+		let hash_size = hash_words::<H>() * WORD;
+		let mut head = Input::new(data);
+		let root = decode_hash(&mut head, hash_size)?;
+		let non_empty_leaves_count = match decode_uint(&mut head)? {
+			0 => None,
+			n => Some(u32::try_from(n).map_err(|_| Error::InvalidLength)?),
+		};
+		let mut indices = tail(data, decode_uint(&mut head)?)?;
+		let mut leaves = tail(data, decode_uint(&mut head)?)?;
+		let mut proof = tail(data, decode_uint(&mut head)?)?;
+		let leaves_count = decode_uint(&mut indices)?;
+
+		if decode_uint(&mut leaves)? != leaves_count {
+			return Err(Error::InvalidLength);
+		}
+		if !matches!(leaves_count.checked_mul(hash_size), Some(size) if size <= leaves.remaining())
+		{
+			return Err(Error::UnexpectedEof);
+		}
+
+		let mut leaves_with_index = Vec::with_capacity(leaves_count);
+
+		for _ in 0..leaves_count {
+			let i = decode_uint(&mut indices)?;
+
+			if i > u32::MAX as usize {
+				return Err(Error::InvalidLength);
+			}
+
+			leaves_with_index.push((i as u32, decode_hash(&mut leaves, hash_size)?));
+		}
+
+		let proof_count = decode_uint(&mut proof)?;
+
+		if !matches!(proof_count.checked_mul(hash_size), Some(size) if size <= proof.remaining()) {
+			return Err(Error::UnexpectedEof);
+		}
+
+		let proof_ = (0..proof_count)
+			.map(|_| decode_hash(&mut proof, hash_size))
+			.collect::<Result<_>>()?;
+
+		// The proof is the last tail of a canonical encoding.
+		proof.finish()?;
+
+		Ok(Proof {
+			root,
+			leaves_with_index,
+			proof: proof_,
+			non_empty_leaves_count,
+			_merge: PhantomData,
+		})
+	}
+}
+
+fn hash_words<H>() -> usize
+where
+	H: AsRef<[u8]> + Default,
+{
+	hash_len::<H>().div_ceil(WORD)
+}
+
+fn encode_uint(n: usize, data: &mut Vec<u8>) {
+	data.extend_from_slice(&[0; WORD - 8]);
+	data.extend_from_slice(&(n as u64).to_be_bytes());
+}
+
+fn encode_hash<H>(hash: &H, hash_size: usize, data: &mut Vec<u8>)
+where
+	H: AsRef<[u8]>,
+{
+	let hash = hash.as_ref();
+
+	data.extend_from_slice(hash);
+	(hash.len()..hash_size).for_each(|_| data.push(0));
+}
+
+fn decode_uint(input: &mut Input) -> Result<usize> {
+	let word = input.read(WORD)?;
+
+	if word[..WORD - 8].iter().any(|b| *b != 0) {
+		return Err(Error::InvalidLength);
+	}
+
+	let mut bytes = [0; 8];
+
+	bytes.copy_from_slice(&word[WORD - 8..]);
+
+	usize::try_from(u64::from_be_bytes(bytes)).map_err(|_| Error::InvalidLength)
+}
+
+fn decode_hash<H>(input: &mut Input, hash_size: usize) -> Result<H>
+where
+	H: AsMut<[u8]> + AsRef<[u8]> + Default,
+{
+	let mut hash = H::default();
+	let len = hash.as_ref().len();
+	let word = input.read(hash_size)?;
+
+	hash.as_mut().copy_from_slice(&word[..len]);
+
+	Ok(hash)
+}
+
+fn tail(data: &[u8], offset: usize) -> Result<Input<'_>> {
+	data.get(offset..)
+		.map(Input::new)
+		.ok_or(Error::InvalidLength)
+}
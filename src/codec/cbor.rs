@@ -0,0 +1,147 @@
+//! The [CBOR](https://www.rfc-editor.org/rfc/rfc8949) format.
+//!
+//! ```text
+//! [
+//!     root: bstr,
+//!     leaves: [[index: uint, leaf: bstr]*],
+//!     proof: [sibling: bstr*],
+//!     leaves count: uint / null,
+//! ]
+//! ```
+//!
+//! All the arrays are in the definite length, the hashes must be exactly `H` sized.
+//! The leaves count is only present in the proofs of the committed root.
+
+// --- core ---
+use core::marker::PhantomData;
+// --- alloc ---
+use alloc::vec::Vec;
+// --- crates.io ---
+use minicbor::{data::Type, decode, Decoder, Encoder};
+// --- sparse-merkle-tree ---
+use crate::{codec::*, Proof};
+
+pub struct Cbor;
+impl<H> ProofCodec<H> for Cbor
+where
+	H: AsMut<[u8]> + AsRef<[u8]> + Default,
+{
+	/// The CBOR integers are always big-endian.
+	const ENDIANNESS: Endianness = Endianness::Big;
+
+	fn encode<M>(proof: &Proof<H, M>) -> Vec<u8> {
+		let mut encoder = Encoder::new(Vec::new());
+
+		encode_proof(&mut encoder, proof).expect("writing into a `Vec` never fails; qed");
+
+		encoder.into_writer()
+	}
+
+	fn decode<M>(data: &[u8]) -> Result<Proof<H, M>> {
+		let mut decoder = Decoder::new(data);
+
+		decode_array(&mut decoder, 4)?;
+
+		let root = decode_hash(&mut decoder)?;
+		let leaves_count = decode_any_array(&mut decoder)?;
+		let mut leaves_with_index = Vec::new();
+
+		for _ in 0..leaves_count {
+			decode_array(&mut decoder, 2)?;
+			leaves_with_index.push((decoder.u32()?, decode_hash(&mut decoder)?));
+		}
+
+		let proof_count = decode_any_array(&mut decoder)?;
+		let mut proof = Vec::new();
+
+		for _ in 0..proof_count {
+			proof.push(decode_hash(&mut decoder)?);
+		}
+
+		let non_empty_leaves_count = if decoder.datatype()? == Type::Null {
+			decoder.null()?;
+
+			None
+		} else {
+			Some(decoder.u32()?)
+		};
+
+		if decoder.position() != data.len() {
+			return Err(Error::TrailingBytes);
+		}
+
+		Ok(Proof {
+			root,
+			leaves_with_index,
+			proof,
+			non_empty_leaves_count,
+			_merge: PhantomData,
+		})
+	}
+}
+
+impl From<decode::Error> for Error {
+	fn from(e: decode::Error) -> Self {
+		if e.is_end_of_input() {
+			Error::UnexpectedEof
+		} else {
+			Error::Malformed
+		}
+	}
+}
+
+fn encode_proof<H, M>(
+	encoder: &mut Encoder<Vec<u8>>,
+	proof: &Proof<H, M>,
+) -> core::result::Result<(), minicbor::encode::Error<core::convert::Infallible>>
+where
+	H: AsRef<[u8]>,
+{
+	encoder.array(4)?.bytes(proof.root.as_ref())?;
+	encoder.array(proof.leaves_with_index.len() as _)?;
+
+	for (i, leaf) in &proof.leaves_with_index {
+		encoder.array(2)?.u32(*i)?.bytes(leaf.as_ref())?;
+	}
+
+	encoder.array(proof.proof.len() as _)?;
+
+	for sibling in &proof.proof {
+		encoder.bytes(sibling.as_ref())?;
+	}
+
+	match proof.non_empty_leaves_count {
+		Some(non_empty_leaves_count) => encoder.u32(non_empty_leaves_count)?,
+		None => encoder.null()?,
+	};
+
+	Ok(())
+}
+
+fn decode_array(decoder: &mut Decoder, len: u64) -> Result<()> {
+	if decoder.array()? == Some(len) {
+		Ok(())
+	} else {
+		Err(Error::InvalidLength)
+	}
+}
+
+fn decode_any_array(decoder: &mut Decoder) -> Result<u64> {
+	decoder.array()?.ok_or(Error::InvalidLength)
+}
+
+fn decode_hash<H>(decoder: &mut Decoder) -> Result<H>
+where
+	H: AsMut<[u8]> + Default,
+{
+	let bytes = decoder.bytes()?;
+	let mut hash = H::default();
+
+	if hash.as_mut().len() != bytes.len() {
+		return Err(Error::InvalidLength);
+	}
+
+	hash.as_mut().copy_from_slice(bytes);
+
+	Ok(hash)
+}
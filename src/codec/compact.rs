@@ -0,0 +1,123 @@
+//! The compact binary format.
+//!
+//! ```text
+//! root
+//! leaves count (u32) | (index (u32), leaf)*
+//! proof count (u32)  | sibling*
+//! 0 | 1 | leaves count (u32)
+//! ```
+//!
+//! The trailing leaves count is only present in the proofs of the committed root.
+//!
+//! The integers are little-endian in [`Compact`] and big-endian in [`CompactBe`].
+
+// --- core ---
+use core::marker::PhantomData;
+// --- alloc ---
+use alloc::vec::Vec;
+// --- sparse-merkle-tree ---
+use crate::{codec::*, Proof};
+
+/// The compact format with the integers in the byte order `E`.
+pub struct CompactWith<E>(PhantomData<E>);
+/// Little-endian, the default one.
+pub type Compact = CompactWith<LittleEndian>;
+/// Big-endian, for the Solidity and Go verifiers.
+pub type CompactBe = CompactWith<BigEndian>;
+impl<H, E> ProofCodec<H> for CompactWith<E>
+where
+	H: AsMut<[u8]> + AsRef<[u8]> + Default,
+	E: ByteOrder,
+{
+	const ENDIANNESS: Endianness = E::ENDIANNESS;
+
+	fn encode<M>(proof: &Proof<H, M>) -> Vec<u8> {
+		let hash_len = hash_len::<H>();
+		let mut data = Vec::with_capacity(
+			hash_len
+				+ 4 + proof.leaves_with_index.len() * (4 + hash_len)
+				+ 4 + proof.proof.len() * hash_len
+				+ 5,
+		);
+
+		data.extend_from_slice(proof.root.as_ref());
+		data.extend_from_slice(&E::ENDIANNESS.encode_u32(proof.leaves_with_index.len() as _));
+		proof.leaves_with_index.iter().for_each(|(i, leaf)| {
+			data.extend_from_slice(&E::ENDIANNESS.encode_u32(*i));
+			data.extend_from_slice(leaf.as_ref());
+		});
+		data.extend_from_slice(&E::ENDIANNESS.encode_u32(proof.proof.len() as _));
+		proof
+			.proof
+			.iter()
+			.for_each(|sibling| data.extend_from_slice(sibling.as_ref()));
+		encode_count(proof.non_empty_leaves_count, E::ENDIANNESS, &mut data);
+
+		data
+	}
+
+	fn decode<M>(data: &[u8]) -> Result<Proof<H, M>> {
+		let hash_len = hash_len::<H>();
+		let mut input = Input::new(data);
+		let root = input.read_hash()?;
+		let leaves_count = input.read_u32(E::ENDIANNESS)? as usize;
+
+		if !matches!(leaves_count.checked_mul(4 + hash_len), Some(size) if size <= input.remaining())
+		{
+			return Err(Error::UnexpectedEof);
+		}
+
+		let mut leaves_with_index = Vec::with_capacity(leaves_count);
+
+		for _ in 0..leaves_count {
+			leaves_with_index.push((input.read_u32(E::ENDIANNESS)?, input.read_hash()?));
+		}
+
+		let proof_count = input.read_u32(E::ENDIANNESS)? as usize;
+
+		if !matches!(proof_count.checked_mul(hash_len), Some(size) if size <= input.remaining()) {
+			return Err(Error::UnexpectedEof);
+		}
+
+		let mut proof = Vec::with_capacity(proof_count);
+
+		for _ in 0..proof_count {
+			proof.push(input.read_hash()?);
+		}
+
+		let non_empty_leaves_count = decode_count(&mut input, E::ENDIANNESS)?;
+
+		input.finish()?;
+
+		Ok(Proof {
+			root,
+			leaves_with_index,
+			proof,
+			non_empty_leaves_count,
+			_merge: PhantomData,
+		})
+	}
+}
+
+/// Shared with [`Scale`](crate::codec::Scale), same as the SCALE `Option<u32>` if little-endian.
+pub(crate) fn encode_count(
+	non_empty_leaves_count: Option<u32>,
+	endianness: Endianness,
+	data: &mut Vec<u8>,
+) {
+	match non_empty_leaves_count {
+		Some(non_empty_leaves_count) => {
+			data.push(1);
+			data.extend_from_slice(&endianness.encode_u32(non_empty_leaves_count));
+		}
+		None => data.push(0),
+	}
+}
+
+pub(crate) fn decode_count(input: &mut Input, endianness: Endianness) -> Result<Option<u32>> {
+	match input.read(1)?[0] {
+		0 => Ok(None),
+		1 => Ok(Some(input.read_u32(endianness)?)),
+		_ => Err(Error::InvalidLength),
+	}
+}
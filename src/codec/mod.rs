@@ -0,0 +1,142 @@
+//! Wire formats for [`Proof`].
+//!
+//! Hashes are treated as opaque fixed-size byte strings, the size is taken from
+//! `H::default().as_ref().len()`.
+//!
+//! The byte order of the integers, e.g. the leaf indices, is fixed by each format, see
+//! [`ProofCodec::ENDIANNESS`].
+
+pub mod abi;
+#[cfg(feature = "cbor")]
+pub mod cbor;
+pub mod compact;
+pub mod scale;
+
+pub use abi::Abi;
+#[cfg(feature = "cbor")]
+pub use cbor::Cbor;
+pub use compact::{Compact, CompactBe, CompactWith};
+pub use scale::Scale;
+
+// --- alloc ---
+use alloc::vec::Vec;
+// --- sparse-merkle-tree ---
+use crate::Proof;
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+	/// The input ended before the proof was completely decoded.
+	UnexpectedEof,
+	/// The input contains extra bytes after the proof.
+	TrailingBytes,
+	/// A length or an offset is malformed.
+	InvalidLength,
+	/// The input isn't well-formed in the format, e.g. an unexpected CBOR type.
+	Malformed,
+}
+
+/// The byte order of the integers in a wire format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Endianness {
+	/// The network byte order, used by Solidity and Go's `binary.BigEndian`.
+	Big,
+	/// Used by SCALE and most of the CPUs.
+	Little,
+}
+impl Endianness {
+	pub const fn encode_u32(self, n: u32) -> [u8; 4] {
+		match self {
+			Self::Big => n.to_be_bytes(),
+			Self::Little => n.to_le_bytes(),
+		}
+	}
+
+	pub const fn decode_u32(self, bytes: [u8; 4]) -> u32 {
+		match self {
+			Self::Big => u32::from_be_bytes(bytes),
+			Self::Little => u32::from_le_bytes(bytes),
+		}
+	}
+}
+
+/// [`Endianness`] at the type level, to choose the byte order of a generic codec.
+pub trait ByteOrder {
+	const ENDIANNESS: Endianness;
+}
+pub struct BigEndian;
+impl ByteOrder for BigEndian {
+	const ENDIANNESS: Endianness = Endianness::Big;
+}
+pub struct LittleEndian;
+impl ByteOrder for LittleEndian {
+	const ENDIANNESS: Endianness = Endianness::Little;
+}
+
+pub trait ProofCodec<H> {
+	/// The byte order of the leaf indices and the counts.
+	const ENDIANNESS: Endianness;
+
+	fn encode<M>(proof: &Proof<H, M>) -> Vec<u8>;
+
+	fn decode<M>(data: &[u8]) -> Result<Proof<H, M>>;
+}
+
+pub(crate) fn hash_len<H>() -> usize
+where
+	H: AsRef<[u8]> + Default,
+{
+	H::default().as_ref().len()
+}
+
+pub(crate) struct Input<'a>(&'a [u8]);
+impl<'a> Input<'a> {
+	pub(crate) fn new(data: &'a [u8]) -> Self {
+		Self(data)
+	}
+
+	pub(crate) fn remaining(&self) -> usize {
+		self.0.len()
+	}
+
+	pub(crate) fn read(&mut self, len: usize) -> Result<&'a [u8]> {
+		if self.0.len() < len {
+			return Err(Error::UnexpectedEof);
+		}
+
+		let (l, r) = self.0.split_at(len);
+
+		self.0 = r;
+
+		Ok(l)
+	}
+
+	pub(crate) fn read_u32(&mut self, endianness: Endianness) -> Result<u32> {
+		let mut bytes = [0; 4];
+
+		bytes.copy_from_slice(self.read(4)?);
+
+		Ok(endianness.decode_u32(bytes))
+	}
+
+	pub(crate) fn read_hash<H>(&mut self) -> Result<H>
+	where
+		H: AsMut<[u8]> + AsRef<[u8]> + Default,
+	{
+		let mut hash = H::default();
+		let len = hash.as_ref().len();
+
+		hash.as_mut().copy_from_slice(self.read(len)?);
+
+		Ok(hash)
+	}
+
+	pub(crate) fn finish(self) -> Result<()> {
+		if self.0.is_empty() {
+			Ok(())
+		} else {
+			Err(Error::TrailingBytes)
+		}
+	}
+}
@@ -0,0 +1,140 @@
+//! The [SCALE](https://docs.substrate.io/v3/advanced/scale-codec) format.
+//!
+//! Equivalent to the `parity-scale-codec` encoding of
+//! `(H, Vec<(u32, H)>, Vec<H>, Option<u32>)` where `H` is a fixed-size byte array.
+
+// --- core ---
+use core::marker::PhantomData;
+// --- alloc ---
+use alloc::vec::Vec;
+// --- sparse-merkle-tree ---
+use crate::{
+	codec::{
+		compact::{decode_count, encode_count},
+		*,
+	},
+	Proof,
+};
+
+pub struct Scale;
+impl<H> ProofCodec<H> for Scale
+where
+	H: AsMut<[u8]> + AsRef<[u8]> + Default,
+{
+	/// SCALE is always little-endian.
+	const ENDIANNESS: Endianness = Endianness::Little;
+
+	fn encode<M>(proof: &Proof<H, M>) -> Vec<u8> {
+		let mut data = Vec::new();
+
+		data.extend_from_slice(proof.root.as_ref());
+		encode_compact(proof.leaves_with_index.len() as u32, &mut data);
+		proof.leaves_with_index.iter().for_each(|(i, leaf)| {
+			data.extend_from_slice(&Endianness::Little.encode_u32(*i));
+			data.extend_from_slice(leaf.as_ref());
+		});
+		encode_compact(proof.proof.len() as u32, &mut data);
+		proof
+			.proof
+			.iter()
+			.for_each(|sibling| data.extend_from_slice(sibling.as_ref()));
+		encode_count(proof.non_empty_leaves_count, Endianness::Little, &mut data);
+
+		data
+	}
+
+	fn decode<M>(data: &[u8]) -> Result<Proof<H, M>> {
+		let hash_len = hash_len::<H>();
+		let mut input = Input::new(data);
+		let root = input.read_hash()?;
+		let leaves_count = decode_compact(&mut input)? as usize;
+
+		if !matches!(leaves_count.checked_mul(4 + hash_len), Some(size) if size <= input.remaining())
+		{
+			return Err(Error::UnexpectedEof);
+		}
+
+		let mut leaves_with_index = Vec::with_capacity(leaves_count);
+
+		for _ in 0..leaves_count {
+			leaves_with_index.push((input.read_u32(Endianness::Little)?, input.read_hash()?));
+		}
+
+		let proof_count = decode_compact(&mut input)? as usize;
+
+		if !matches!(proof_count.checked_mul(hash_len), Some(size) if size <= input.remaining()) {
+			return Err(Error::UnexpectedEof);
+		}
+
+		let mut proof = Vec::with_capacity(proof_count);
+
+		for _ in 0..proof_count {
+			proof.push(input.read_hash()?);
+		}
+
+		let non_empty_leaves_count = decode_count(&mut input, Endianness::Little)?;
+
+		input.finish()?;
+
+		Ok(Proof {
+			root,
+			leaves_with_index,
+			proof,
+			non_empty_leaves_count,
+			_merge: PhantomData,
+		})
+	}
+}
+
+fn encode_compact(n: u32, data: &mut Vec<u8>) {
+	match n {
+		0..=0x3f => data.push((n as u8) << 2),
+		0x40..=0x3fff => data.extend_from_slice(&(((n as u16) << 2) | 0b01).to_le_bytes()),
+		0x4000..=0x3fff_ffff => data.extend_from_slice(&((n << 2) | 0b10).to_le_bytes()),
+		_ => {
+			data.push(0b11);
+			data.extend_from_slice(&n.to_le_bytes());
+		}
+	}
+}
+
+fn decode_compact(input: &mut Input) -> Result<u32> {
+	let prefix = input.read(1)?[0];
+
+	match prefix & 0b11 {
+		0b00 => Ok((prefix >> 2) as u32),
+		0b01 => {
+			let n = u16::from_le_bytes([prefix, input.read(1)?[0]]) >> 2;
+
+			if n > 0x3f {
+				Ok(n as u32)
+			} else {
+				Err(Error::InvalidLength)
+			}
+		}
+		0b10 => {
+			let rest = input.read(3)?;
+			let n = u32::from_le_bytes([prefix, rest[0], rest[1], rest[2]]) >> 2;
+
+			if n > 0x3fff {
+				Ok(n)
+			} else {
+				Err(Error::InvalidLength)
+			}
+		}
+		_ => {
+			// Only 4-byte big integers fit into `u32`.
+			if prefix >> 2 != 0 {
+				return Err(Error::InvalidLength);
+			}
+
+			let n = input.read_u32(Endianness::Little)?;
+
+			if n > 0x3fff_ffff {
+				Ok(n)
+			} else {
+				Err(Error::InvalidLength)
+			}
+		}
+	}
+}
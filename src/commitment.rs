@@ -0,0 +1,120 @@
+//! Be generic over the commitment structures.
+
+// --- alloc ---
+use alloc::vec::Vec;
+// --- sparse-merkle-tree ---
+use crate::{
+	kary::{KaryProof, KaryTree, MergeN},
+	virtual_tree::VirtualTree,
+	*,
+};
+
+/// A structure committing to its leaves with a single root.
+///
+/// ```ignore
+/// fn claim<C>(commitment: &C, index: u32) -> Option<C::Proof>
+/// where
+///     C: MerkleCommitment,
+/// {
+///     commitment.prove(&[index])
+/// }
+/// ```
+pub trait MerkleCommitment {
+	type Hash;
+	type Proof;
+
+	fn root(&self) -> Self::Hash;
+
+	/// The proof of the leaves at `indices`, in any order.
+	///
+	/// `None` if `indices` is empty, out of bounds, or contains any duplicate.
+	fn prove(&self, indices: &[u32]) -> Option<Self::Proof>;
+
+	/// Whether the proof matches its own root, compare the root with a trusted one separately.
+	fn verify(proof: Self::Proof) -> bool;
+}
+
+impl<H, M> MerkleCommitment for SparseMerkleTree<H, M>
+where
+	H: Clone + MaybeDebug + PartialEq,
+	M: Merge<Item = H>,
+{
+	type Hash = H;
+	type Proof = Proof<H, M>;
+
+	fn root(&self) -> H {
+		SparseMerkleTree::root(self)
+	}
+
+	/// The leaves of the proof are in the descending order.
+	fn prove(&self, indices: &[u32]) -> Option<Proof<H, M>> {
+		sorted(indices, self.non_empty_leaves_count()).map(|indices| self.proof_of(indices))
+	}
+
+	fn verify(proof: Proof<H, M>) -> bool {
+		SparseMerkleTree::verify(proof)
+	}
+}
+
+impl<H, M> MerkleCommitment for VirtualTree<H, M>
+where
+	H: Clone + MaybeDebug + PartialEq,
+	M: Merge<Item = H>,
+{
+	type Hash = H;
+	type Proof = Proof<H, M>;
+
+	fn root(&self) -> H {
+		VirtualTree::root(self)
+	}
+
+	/// The leaves of the proof are in the descending order.
+	fn prove(&self, indices: &[u32]) -> Option<Proof<H, M>> {
+		sorted(indices, self.non_empty_leaves_count()).map(|indices| self.proof_of(indices))
+	}
+
+	fn verify(proof: Proof<H, M>) -> bool {
+		SparseMerkleTree::verify(proof)
+	}
+}
+
+impl<H, M, const ARITY: usize> MerkleCommitment for KaryTree<H, M, ARITY>
+where
+	H: Clone + MaybeDebug + PartialEq,
+	M: MergeN<Item = H>,
+{
+	type Hash = H;
+	type Proof = KaryProof<H, M, ARITY>;
+
+	fn root(&self) -> H {
+		KaryTree::root(self)
+	}
+
+	fn prove(&self, indices: &[u32]) -> Option<KaryProof<H, M, ARITY>> {
+		if indices.is_empty() {
+			None
+		} else {
+			self.proof_of(indices)
+		}
+	}
+
+	fn verify(proof: KaryProof<H, M, ARITY>) -> bool {
+		KaryTree::verify(&proof)
+	}
+}
+
+/// `indices` in the descending order, `None` if any of them is out of bounds or duplicate.
+fn sorted(indices: &[u32], non_empty_leaves_count: u32) -> Option<Vec<u32>> {
+	let mut sorted = indices.to_vec();
+
+	sorted.sort_unstable_by(|a, b| b.cmp(a));
+
+	if sorted.is_empty()
+		|| sorted[0] >= non_empty_leaves_count
+		|| sorted.windows(2).any(|pair| pair[0] == pair[1])
+	{
+		None
+	} else {
+		Some(sorted)
+	}
+}
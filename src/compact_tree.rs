@@ -0,0 +1,119 @@
+//! Drop the leaf layer once the tree is built.
+
+// --- core ---
+use core::marker::PhantomData;
+// --- alloc ---
+use alloc::vec::Vec;
+// --- sparse-merkle-tree ---
+use crate::*;
+
+/// A [`SparseMerkleTree`] without the leaf layer, which takes half of the memory.
+///
+/// The lowest kept level becomes the proof granularity, each node there covers
+/// [`CompactTree::leaves_per_node`] leaves. The proofs are verified by
+/// [`SparseMerkleTree::verify`] as usual.
+pub struct CompactTree<H, M> {
+	nodes: Vec<H>,
+	non_empty_leaves_count: u32,
+	leaves_per_node: u32,
+	_merge: PhantomData<M>,
+}
+impl<H, M> CompactTree<H, M>
+where
+	H: Clone + MaybeDebug + PartialEq,
+	M: Merge<Item = H>,
+{
+	pub fn root(&self) -> H {
+		self.nodes.get(1).cloned().unwrap_or_else(M::empty)
+	}
+
+	pub fn non_empty_leaves_count(&self) -> u32 {
+		self.non_empty_leaves_count
+	}
+
+	/// `2`, unless the tree has only one leaf.
+	pub fn leaves_per_node(&self) -> u32 {
+		self.leaves_per_node
+	}
+
+	/// The number of the lowest level nodes which cover at least one non-empty leaf.
+	pub fn non_empty_nodes_count(&self) -> u32 {
+		self.non_empty_leaves_count.div_ceil(self.leaves_per_node())
+	}
+
+	/// The node at `index` of the lowest level.
+	pub fn node(&self, index: u32) -> Option<&H> {
+		if index < self.non_empty_nodes_count() {
+			self.nodes.get((self.lowest_level_len() + index) as usize)
+		} else {
+			None
+		}
+	}
+
+	/// Same as [`SparseMerkleTree::proof_of`], but `indices` are the lowest level ones.
+	///
+	/// The leaves `index * leaves_per_node..(index + 1) * leaves_per_node` are proven at once.
+	pub fn proof_of<I>(&self, indices: I) -> Proof<H, M>
+	where
+		I: IntoIterator,
+		I::Item: Borrow<u32>,
+	{
+		let indices = collect_indices(indices);
+
+		if indices.iter().any(|i| *i >= self.non_empty_nodes_count()) {
+			warn!("proof_of::Index out of bounds.");
+
+			return Proof::default();
+		}
+
+		let half_nodes_count = self.lowest_level_len();
+
+		Proof {
+			root: self.root(),
+			leaves_with_index: indices
+				.iter()
+				.map(|i| {
+					let i = half_nodes_count + *i;
+
+					(i, self.nodes[i as usize].clone())
+				})
+				.collect(),
+			proof: siblings_of(&self.nodes, &indices, &mut ProofScratch::new()),
+			non_empty_leaves_count: None,
+			_merge: PhantomData,
+		}
+	}
+
+	fn lowest_level_len(&self) -> u32 {
+		self.nodes.len() as u32 / 2
+	}
+}
+
+impl<H, M> SparseMerkleTree<H, M>
+where
+	H: Clone + MaybeDebug + PartialEq,
+	M: Merge<Item = H>,
+{
+	/// Free the leaf layer, see [`CompactTree`].
+	pub fn finalize(mut self) -> CompactTree<H, M> {
+		// A single leaf tree has no internal node, keep the leaf as the root.
+		let len = self.capacity().max(2);
+		// The empty tree has no leaf, treat it as a single leaf one.
+		let leaves_per_node = (self.capacity() / (len / 2)).max(1);
+
+		self.nodes.truncate(len as _);
+		self.nodes.shrink_to_fit();
+
+		CompactTree {
+			nodes: self.nodes,
+			non_empty_leaves_count: self.non_empty_leaves_count,
+			leaves_per_node,
+			_merge: PhantomData,
+		}
+	}
+
+	/// Drop everything but the root, if the proofs are no longer needed.
+	pub fn into_root(self) -> H {
+		self.root()
+	}
+}
@@ -0,0 +1,348 @@
+//! Collapse the fully proven subtrees, for the dense claims like "the first 1024 leaves".
+//!
+//! A [`Proof`] lists the index of every proven leaf. If all the leaves of a subtree are proven,
+//! [`CompressedProof`] lists the subtree once instead, its leaves follow in order. The siblings
+//! are the same, none of them is inside a fully proven subtree.
+//!
+//! The verification collapses each subtree into a single node, its root is recomputed from the
+//! leaves in place. Then the multiproof runs over these nodes, which are in different depths,
+//! instead of over every single leaf.
+//!
+//! The wire format, the integers are in the byte order of `E`:
+//!
+//! ```text
+//! root
+//! subtrees count (u32) | (node index (u32), height (u8), leaf * 2^height)*
+//! proof count (u32)    | sibling*
+//! 0 | 1 | leaves count (u32)
+//! ```
+
+// --- core ---
+use core::marker::PhantomData;
+// --- alloc ---
+use alloc::{collections::BTreeMap, vec::Vec};
+// --- sparse-merkle-tree ---
+use crate::{
+	codec::{
+		self,
+		compact::{decode_count, encode_count},
+		hash_len, ByteOrder, Input,
+	},
+	*,
+};
+
+/// A fully proven subtree, the node index of its root and its leaves in order.
+pub type Subtree<H> = (u32, Vec<H>);
+
+/// A [`Proof`] whose fully proven subtrees are collapsed.
+pub struct CompressedProof<H, M> {
+	root: H,
+	/// In the ascending leaf order.
+	subtrees: Vec<Subtree<H>>,
+	proof: Vec<H>,
+	non_empty_leaves_count: Option<u32>,
+	_merge: PhantomData<M>,
+}
+// Implement these manually, the derived ones would require `M` to implement them too.
+impl<H, M> Clone for CompressedProof<H, M>
+where
+	H: Clone,
+{
+	fn clone(&self) -> Self {
+		Self {
+			root: self.root.clone(),
+			subtrees: self.subtrees.clone(),
+			proof: self.proof.clone(),
+			non_empty_leaves_count: self.non_empty_leaves_count,
+			_merge: PhantomData,
+		}
+	}
+}
+impl<H, M> PartialEq for CompressedProof<H, M>
+where
+	H: PartialEq,
+{
+	fn eq(&self, other: &Self) -> bool {
+		self.root == other.root
+			&& self.subtrees == other.subtrees
+			&& self.proof == other.proof
+			&& self.non_empty_leaves_count == other.non_empty_leaves_count
+	}
+}
+impl<H, M> CompressedProof<H, M>
+where
+	H: Clone,
+{
+	pub fn root(&self) -> &H {
+		&self.root
+	}
+
+	pub fn subtrees(&self) -> &[Subtree<H>] {
+		&self.subtrees
+	}
+
+	/// Expand the subtrees, the leaves are in the descending order as the verification requires.
+	///
+	/// `None` if a subtree isn't a power of two leaves, or is out of the `u32` node indices.
+	pub fn decompress(self) -> Option<Proof<H, M>> {
+		let mut leaves_with_index = Vec::new();
+
+		for (i, leaves) in self.subtrees.into_iter().rev() {
+			if !leaves.len().is_power_of_two() {
+				return None;
+			}
+
+			let height = leaves.len().trailing_zeros();
+			let first = i.checked_shl(height).filter(|first| first >> height == i)?;
+
+			leaves_with_index.extend(
+				leaves
+					.into_iter()
+					.enumerate()
+					.rev()
+					.map(|(k, leaf)| (first + k as u32, leaf)),
+			);
+		}
+
+		Some(Proof {
+			root: self.root,
+			leaves_with_index,
+			proof: self.proof,
+			non_empty_leaves_count: self.non_empty_leaves_count,
+			_merge: PhantomData,
+		})
+	}
+
+	pub fn encode<E>(&self) -> Vec<u8>
+	where
+		H: AsRef<[u8]>,
+		E: ByteOrder,
+	{
+		let endianness = E::ENDIANNESS;
+		let mut data = Vec::new();
+
+		data.extend_from_slice(self.root.as_ref());
+		data.extend_from_slice(&endianness.encode_u32(self.subtrees.len() as _));
+		self.subtrees.iter().for_each(|(i, leaves)| {
+			data.extend_from_slice(&endianness.encode_u32(*i));
+			data.push(leaves.len().trailing_zeros() as _);
+			leaves
+				.iter()
+				.for_each(|leaf| data.extend_from_slice(leaf.as_ref()));
+		});
+		data.extend_from_slice(&endianness.encode_u32(self.proof.len() as _));
+		self.proof
+			.iter()
+			.for_each(|sibling| data.extend_from_slice(sibling.as_ref()));
+		encode_count(self.non_empty_leaves_count, endianness, &mut data);
+
+		data
+	}
+
+	pub fn decode<E>(data: &[u8]) -> codec::Result<Self>
+	where
+		H: AsMut<[u8]> + AsRef<[u8]> + Default,
+		E: ByteOrder,
+	{
+		let endianness = E::ENDIANNESS;
+		let hash_len = hash_len::<H>();
+		let mut input = Input::new(data);
+		let root = input.read_hash()?;
+		let subtrees_count = input.read_u32(endianness)? as usize;
+		let mut subtrees = Vec::new();
+
+		for _ in 0..subtrees_count {
+			let i = input.read_u32(endianness)?;
+			let leaves_count = 1_usize
+				.checked_shl(input.read(1)?[0] as _)
+				.ok_or(codec::Error::InvalidLength)?;
+
+			if leaves_count
+				.checked_mul(hash_len)
+				.is_none_or(|len| input.remaining() < len)
+			{
+				return Err(codec::Error::UnexpectedEof);
+			}
+
+			let leaves = (0..leaves_count)
+				.map(|_| input.read_hash())
+				.collect::<codec::Result<_>>()?;
+
+			subtrees.push((i, leaves));
+		}
+
+		let proof_count = input.read_u32(endianness)? as usize;
+
+		if proof_count
+			.checked_mul(hash_len)
+			.is_none_or(|len| input.remaining() < len)
+		{
+			return Err(codec::Error::UnexpectedEof);
+		}
+
+		let proof = (0..proof_count)
+			.map(|_| input.read_hash())
+			.collect::<codec::Result<_>>()?;
+		let non_empty_leaves_count = decode_count(&mut input, endianness)?;
+
+		input.finish()?;
+
+		Ok(Self {
+			root,
+			subtrees,
+			proof,
+			non_empty_leaves_count,
+			_merge: PhantomData,
+		})
+	}
+}
+
+impl<H, M> Proof<H, M>
+where
+	H: Clone + PartialEq,
+{
+	/// Collapse the fully proven subtrees, the leaves could be in any order.
+	///
+	/// `None` if the leaves are in different depths, or an index has different leaves.
+	pub fn compress(&self) -> Option<CompressedProof<H, M>> {
+		let depth = self.depth()?;
+		let mut leaves = self.leaves_with_index.clone();
+
+		if leaves.iter().any(|(i, _)| node_depth(*i) != Some(depth)) {
+			return None;
+		}
+
+		leaves.sort_by_key(|(i, _)| *i);
+		leaves.dedup();
+
+		if leaves.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+			return None;
+		}
+
+		let mut subtrees = Vec::new();
+		let mut k = 0;
+
+		while k < leaves.len() {
+			let first = leaves[k].0;
+			let mut height = 0;
+
+			// Grow the subtree while it's aligned, and the leaves of its right sibling are proven.
+			while height < depth
+				&& first.trailing_zeros() > height
+				&& leaves.get(k + (2 << height) - 1).map(|(i, _)| *i)
+					== Some(first + (2 << height) - 1)
+			{
+				height += 1;
+			}
+
+			let subtree = leaves[k..k + (1 << height)]
+				.iter()
+				.map(|(_, leaf)| leaf.clone())
+				.collect();
+
+			subtrees.push((first >> height, subtree));
+			k += 1 << height;
+		}
+
+		Some(CompressedProof {
+			root: self.root.clone(),
+			subtrees,
+			proof: self.proof.clone(),
+			non_empty_leaves_count: self.non_empty_leaves_count,
+			_merge: PhantomData,
+		})
+	}
+}
+
+impl<H, M> SparseMerkleTree<H, M>
+where
+	H: Clone + MaybeDebug + PartialEq,
+	M: Merge<Item = H>,
+{
+	/// Same as [`SparseMerkleTree::proof_of`], but compressed.
+	///
+	/// `None` if any index is out of bounds.
+	pub fn compressed_proof_of<I>(&self, indices: I) -> Option<CompressedProof<H, M>>
+	where
+		I: IntoIterator,
+		I::Item: Borrow<u32>,
+	{
+		self.proof_of(indices).compress()
+	}
+
+	/// Verify without decompressing, see the [module docs](self).
+	pub fn verify_compressed(proof: CompressedProof<H, M>) -> bool {
+		proof.compute_root().as_ref() == Some(&proof.root)
+	}
+}
+
+impl<H, M> CompressedProof<H, M>
+where
+	H: Clone + MaybeDebug + PartialEq,
+	M: Merge<Item = H>,
+{
+	/// `None` if the proof is malformed, e.g. the subtrees overlap or their leaves are in
+	/// different depths, the siblings are missing or left over.
+	fn compute_root(&self) -> Option<H> {
+		let mut depth = None;
+		// The collapsed subtrees, then the merged nodes, by the node index.
+		let mut nodes = BTreeMap::new();
+
+		for (i, leaves) in &self.subtrees {
+			if *i == 0 || !leaves.len().is_power_of_two() {
+				return None;
+			}
+
+			let height = leaves.len().trailing_zeros();
+			let leaves_depth = i
+				.checked_shl(height)
+				.filter(|first| first >> height == *i)
+				.and_then(node_depth)?;
+
+			if depth
+				.replace(leaves_depth)
+				.is_some_and(|depth| depth != leaves_depth)
+			{
+				return None;
+			}
+
+			let mut level = leaves.clone();
+
+			while level.len() > 1 {
+				level = level
+					.chunks(2)
+					.map(|pair| M::merge(&pair[0], &pair[1]))
+					.collect();
+			}
+
+			if nodes.insert(*i, level.pop()?).is_some() {
+				return None;
+			}
+		}
+
+		let mut siblings = self.proof.iter();
+
+		// The deepest and the rightmost node first, the same order as the siblings.
+		while let Some((i, node)) = nodes.pop_last() {
+			if i == 1 {
+				// All the siblings must be consumed.
+				return siblings.next().is_none().then_some(node);
+			}
+
+			let parent = if i & 1 == 0 {
+				M::merge(&node, siblings.next()?)
+			} else if let Some(l) = nodes.remove(&(i - 1)) {
+				M::merge(&l, &node)
+			} else {
+				M::merge(siblings.next()?, &node)
+			};
+
+			// A subtree inside another one.
+			if nodes.insert(i / 2, parent).is_some() {
+				return None;
+			}
+		}
+
+		None
+	}
+}
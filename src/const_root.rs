@@ -0,0 +1,45 @@
+//! Compute the root at compile time.
+//!
+//! Trait methods can't be called in the const contexts, so [`Merge`](crate::Merge) can't be
+//! used here. Pass a `const fn(&H, &H) -> H` instead.
+
+/// The root of the leaves, same as [`SparseMerkleTree::root`](crate::SparseMerkleTree::root).
+///
+/// Both `H` and the expression of the empty leaf must be `Copy`.
+///
+/// ```
+/// use tiny_sparse_merkle_tree::const_root;
+///
+/// const fn merge(l: &u32, r: &u32) -> u32 {
+///     2 * *l + *r
+/// }
+///
+/// const GENESIS_ROOT: u32 = const_root!(merge, 0; [1, 2, 3]);
+///
+/// assert_eq!(GENESIS_ROOT, 2 * (2 * 1 + 2) + (2 * 3 + 0));
+/// ```
+#[macro_export]
+macro_rules! const_root {
+	($merge:path, $empty:expr; [$($leaf:expr),* $(,)?]) => {{
+		const LEAVES_COUNT: usize = <[&str]>::len(&[$(stringify!($leaf)),*]);
+		const HALF_LEAVES_COUNT: usize = LEAVES_COUNT.next_power_of_two();
+
+		let leaves: [_; LEAVES_COUNT] = [$($leaf),*];
+		let mut nodes = [$empty; HALF_LEAVES_COUNT * 2];
+		let mut i = 0;
+
+		while i < LEAVES_COUNT {
+			nodes[HALF_LEAVES_COUNT + i] = leaves[i];
+			i += 1;
+		}
+
+		i = HALF_LEAVES_COUNT - 1;
+
+		while i > 0 {
+			nodes[i] = $merge(&nodes[i * 2], &nodes[i * 2 + 1]);
+			i -= 1;
+		}
+
+		nodes[1]
+	}};
+}
@@ -0,0 +1,104 @@
+//! Choose the merger at runtime.
+//!
+//! [`Merge`] has no receiver, so it can't be made into a trait object.
+//! [`DynMerge`] is the object-safe counterpart, trees and proofs built through it are typed with
+//! the [`Dyn`] marker.
+
+// --- core ---
+use core::{borrow::Borrow, marker::PhantomData};
+// --- sparse-merkle-tree ---
+use crate::{collect_indices, MaybeDebug, Merge, Proof, ProofScratch, SparseMerkleTree};
+
+pub trait DynMerge<H> {
+	/// The empty leaf, which is used to pad the tree.
+	fn empty(&self) -> H;
+
+	fn merge(&self, l: &H, r: &H) -> H;
+
+	/// Write the merge result into an existing node.
+	fn merge_into(&self, out: &mut H, l: &H, r: &H) {
+		*out = self.merge(l, r);
+	}
+}
+
+/// Turn a static [`Merge`] into a [`DynMerge`].
+///
+/// ```ignore
+/// let merger: &dyn DynMerge<[u8; 32]> = &MergeAdapter::<Keccak256>::new();
+/// ```
+pub struct MergeAdapter<M>(PhantomData<M>);
+// Implement these manually, the derived ones would require `M` to implement them too.
+impl<M> Clone for MergeAdapter<M> {
+	fn clone(&self) -> Self {
+		*self
+	}
+}
+impl<M> Copy for MergeAdapter<M> {}
+impl<M> Default for MergeAdapter<M> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+impl<M> MergeAdapter<M> {
+	pub const fn new() -> Self {
+		Self(PhantomData)
+	}
+}
+impl<M> DynMerge<M::Item> for MergeAdapter<M>
+where
+	M: Merge,
+{
+	fn empty(&self) -> M::Item {
+		M::empty()
+	}
+
+	fn merge(&self, l: &M::Item, r: &M::Item) -> M::Item {
+		M::merge(l, r)
+	}
+
+	fn merge_into(&self, out: &mut M::Item, l: &M::Item, r: &M::Item) {
+		M::merge_into(out, l, r)
+	}
+}
+
+/// The merger of a tree or a proof is chosen at runtime.
+///
+/// Nothing stops a [`Dyn`] proof from being verified with another merger than the one which built
+/// the tree, the caller has to keep them consistent.
+pub struct Dyn;
+
+impl<H> SparseMerkleTree<H, Dyn>
+where
+	H: Clone + MaybeDebug + PartialEq,
+{
+	pub fn new_dyn<L>(leaves: L, merger: &dyn DynMerge<H>) -> Self
+	where
+		L: IntoIterator<Item = H>,
+	{
+		Self::build(merger, leaves, Default::default(), None, None, &mut ())
+	}
+
+	pub fn root_dyn(&self, merger: &dyn DynMerge<H>) -> H {
+		self.root_inner(merger)
+	}
+
+	/// Same as [`SparseMerkleTree::proof_of`], `merger` is only used for the empty root.
+	pub fn proof_of_dyn<I>(&self, indices: I, merger: &dyn DynMerge<H>) -> Proof<H, Dyn>
+	where
+		I: IntoIterator,
+		I::Item: Borrow<u32>,
+	{
+		self.proof_of_inner(&collect_indices(indices), merger, &mut ProofScratch::new())
+	}
+
+	pub fn verify_dyn(proof: Proof<H, Dyn>, merger: &dyn DynMerge<H>) -> bool {
+		let Proof {
+			root,
+			leaves_with_index,
+			proof,
+			..
+		} = proof;
+
+		Self::compute_root(merger, leaves_with_index, &proof, &mut ()).as_ref() == Some(&root)
+	}
+}
@@ -0,0 +1,138 @@
+//! Import the single leaf proofs built by the other tools.
+//!
+//! Only the layout is converted, the merger `M` must hash the same way as the foreign tree, e.g.
+//! with the domain separation prefixes of RFC 6962. The node index of the leaf is derived from the
+//! sides of the siblings, it's not the leaf index of the foreign tree.
+
+// --- core ---
+use core::marker::PhantomData;
+// --- alloc ---
+use alloc::vec::Vec;
+// --- sparse-merkle-tree ---
+use crate::*;
+
+/// The side of a sibling, relative to the node being proven.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+	Left,
+	Right,
+}
+
+/// Merge the sorted pair, as the OpenZeppelin `MerkleProof` does.
+pub struct SortedPair<M>(PhantomData<M>);
+impl<M> Merge for SortedPair<M>
+where
+	M: Merge,
+	M::Item: Ord,
+{
+	type Item = M::Item;
+
+	fn empty() -> Self::Item {
+		M::empty()
+	}
+
+	fn merge(l: &Self::Item, r: &Self::Item) -> Self::Item {
+		if l <= r {
+			M::merge(l, r)
+		} else {
+			M::merge(r, l)
+		}
+	}
+}
+
+/// A path of the explicit sides from the leaf up, e.g. the `merkletreejs` `getProof` output
+/// `[{ position: "left" | "right", data }]`.
+///
+/// `None` if the path is deeper than [`MAX_DEPTH`].
+pub fn from_path<H, M, P>(root: H, leaf: H, path: P) -> Option<Proof<H, M>>
+where
+	P: IntoIterator<Item = (Side, H)>,
+{
+	let mut i = 0_u32;
+	let mut proof = Vec::new();
+
+	for (height, (side, sibling)) in path.into_iter().enumerate() {
+		if height as u32 >= MAX_DEPTH {
+			return None;
+		}
+		// The proven node is the right child.
+		if side == Side::Left {
+			i |= 1 << height;
+		}
+
+		proof.push(sibling);
+	}
+
+	// The leading `1` of the node index.
+	i |= 1 << proof.len();
+
+	Some(Proof {
+		root,
+		leaves_with_index: [(i, leaf)].into(),
+		proof,
+		non_empty_leaves_count: None,
+		_merge: PhantomData,
+	})
+}
+
+/// An OpenZeppelin sorted-pair path from the leaf up, the sides don't matter.
+pub fn from_sorted_pair_path<H, M>(
+	root: H,
+	leaf: H,
+	path: Vec<H>,
+) -> Option<Proof<H, SortedPair<M>>> {
+	from_path(
+		root,
+		leaf,
+		path.into_iter().map(|sibling| (Side::Right, sibling)),
+	)
+}
+
+/// An RFC 6962 audit path of the leaf at `leaf_index` in the tree of `tree_size`.
+///
+/// `leaf` is the leaf hash, `M` must merge as `SHA-256(0x01 || l || r)`.
+/// `None` if the path doesn't match the position, see RFC 9162 section 2.1.3.2.
+pub fn from_rfc6962_audit_path<H, M>(
+	root: H,
+	leaf: H,
+	leaf_index: u64,
+	tree_size: u64,
+	path: Vec<H>,
+) -> Option<Proof<H, M>> {
+	if leaf_index >= tree_size {
+		return None;
+	}
+
+	let mut f = leaf_index;
+	let mut s = tree_size - 1;
+	let mut sides = Vec::with_capacity(path.len());
+
+	for _ in &path {
+		if s == 0 {
+			return None;
+		}
+
+		if f & 1 == 1 || f == s {
+			sides.push(Side::Left);
+
+			// The right edge of an unbalanced tree, it's promoted without a sibling.
+			if f & 1 == 0 {
+				while f & 1 == 0 && f != 0 {
+					f >>= 1;
+					s >>= 1;
+				}
+			}
+		} else {
+			sides.push(Side::Right);
+		}
+
+		f >>= 1;
+		s >>= 1;
+	}
+
+	if s != 0 {
+		return None;
+	}
+
+	from_path(root, leaf, sides.into_iter().zip(path))
+}
@@ -0,0 +1,162 @@
+//! Many trees sharing the same merger and default nodes.
+
+// --- alloc ---
+use alloc::collections::BTreeMap;
+// --- sparse-merkle-tree ---
+use crate::*;
+
+/// A collection of trees keyed by `K`, e.g. one tree per epoch or per channel.
+///
+/// The top-level root commits to all the tree roots in the key order.
+pub struct Forest<K, H, M> {
+	trees: BTreeMap<K, SparseMerkleTree<H, M>>,
+	default_nodes: DefaultNodes<H, M>,
+}
+impl<K, H, M> Forest<K, H, M>
+where
+	K: Ord,
+	H: Clone + MaybeDebug + PartialEq,
+	M: Merge<Item = H>,
+{
+	/// `max_height` is the height of the highest empty subtree which could be shared.
+	pub fn new(max_height: u32) -> Self {
+		Self {
+			trees: BTreeMap::new(),
+			default_nodes: DefaultNodes::new(max_height),
+		}
+	}
+
+	pub fn default_nodes(&self) -> &DefaultNodes<H, M> {
+		&self.default_nodes
+	}
+
+	/// Build a tree from `leaves` and insert it, return the replaced one if any.
+	pub fn insert<L>(&mut self, key: K, leaves: L) -> Option<SparseMerkleTree<H, M>>
+	where
+		L: IntoIterator<Item = H>,
+	{
+		self.trees.insert(
+			key,
+			SparseMerkleTree::new_with_default_nodes(leaves, &self.default_nodes),
+		)
+	}
+
+	pub fn remove(&mut self, key: &K) -> Option<SparseMerkleTree<H, M>> {
+		self.trees.remove(key)
+	}
+
+	pub fn get(&self, key: &K) -> Option<&SparseMerkleTree<H, M>> {
+		self.trees.get(key)
+	}
+
+	pub fn len(&self) -> usize {
+		self.trees.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.trees.is_empty()
+	}
+
+	pub fn root_of(&self, key: &K) -> Option<H> {
+		self.trees.get(key).map(SparseMerkleTree::root)
+	}
+
+	pub fn proof_of<I>(&self, key: &K, indices: I) -> Option<Proof<H, M>>
+	where
+		I: IntoIterator,
+		I::Item: Borrow<u32>,
+	{
+		self.trees.get(key).map(|tree| tree.proof_of(indices))
+	}
+
+	/// The tree whose leaves are the tree roots in the key order.
+	pub fn roots_tree(&self) -> SparseMerkleTree<H, M> {
+		SparseMerkleTree::new_with_default_nodes(
+			self.trees.values().map(SparseMerkleTree::root),
+			&self.default_nodes,
+		)
+	}
+
+	pub fn root(&self) -> H {
+		self.roots_tree().root()
+	}
+
+	/// Prove that the tree root of `key` is committed by [`Forest::root`].
+	pub fn proof_of_tree(&self, key: &K) -> Option<Proof<H, M>> {
+		let i = self.trees.keys().position(|k| k == key)?;
+
+		Some(self.roots_tree().proof_of([i as u32]))
+	}
+
+	/// Prove the leaves of the tree of `key` against [`Forest::root`] at once.
+	pub fn aggregated_proof_of<I>(&self, key: &K, indices: I) -> Option<AggregatedProof<H, M>>
+	where
+		I: IntoIterator,
+		I::Item: Borrow<u32>,
+	{
+		Some(AggregatedProof::new(
+			self.proof_of(key, indices)?,
+			self.proof_of_tree(key)?,
+		))
+	}
+}
+
+/// A leaves proof in a child tree, chained with the proof of the child root in the parent tree.
+pub struct AggregatedProof<H, M> {
+	pub leaves_proof: Proof<H, M>,
+	pub tree_proof: Proof<H, M>,
+}
+// Implement this manually, the derived one would require `M` to implement it too.
+impl<H, M> Clone for AggregatedProof<H, M>
+where
+	H: Clone,
+{
+	fn clone(&self) -> Self {
+		Self {
+			leaves_proof: self.leaves_proof.clone(),
+			tree_proof: self.tree_proof.clone(),
+		}
+	}
+}
+impl<H, M> AggregatedProof<H, M> {
+	pub fn new(leaves_proof: Proof<H, M>, tree_proof: Proof<H, M>) -> Self {
+		Self {
+			leaves_proof,
+			tree_proof,
+		}
+	}
+
+	/// The root of the parent tree.
+	pub fn root(&self) -> &H {
+		&self.tree_proof.root
+	}
+
+	/// The index of the child tree in the parent tree, `None` if the tree proof is malformed.
+	pub fn tree_index(&self) -> Option<u32> {
+		match self.tree_proof.leaves_with_index.as_slice() {
+			[(i, _)] => Some(*i - node_depth(*i).map(|depth| 1 << depth)?),
+			_ => None,
+		}
+	}
+}
+
+impl<H, M> SparseMerkleTree<H, M>
+where
+	H: Clone + MaybeDebug + PartialEq,
+	M: Merge<Item = H>,
+{
+	/// Verify both proofs, and that the child root is the only leaf proven by the tree proof.
+	pub fn verify_aggregated(proof: AggregatedProof<H, M>) -> bool {
+		let AggregatedProof {
+			leaves_proof,
+			tree_proof,
+		} = proof;
+
+		match tree_proof.leaves_with_index.as_slice() {
+			[(_, child_root)] if child_root == &leaves_proof.root => {}
+			_ => return false,
+		}
+
+		Self::verify(leaves_proof) && Self::verify(tree_proof)
+	}
+}
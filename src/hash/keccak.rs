@@ -1,7 +1,10 @@
 // --- crates.io ---
 use tiny_keccak::{Hasher as _, Keccak};
 // --- sparse-merkle-tree ---
-use crate::{hash::Hasher, *};
+use crate::{
+	hash::{self, Hasher, IncrementalHasher},
+	*,
+};
 
 pub type Hash = [u8; 32];
 
@@ -22,15 +25,55 @@ impl Hasher for Keccak256 {
 		output
 	}
 }
+impl IncrementalHasher for Keccak256 {
+	type State = Keccak;
+
+	fn hasher() -> Self::State {
+		Keccak::v256()
+	}
+}
+impl hash::Update for Keccak {
+	fn update(&mut self, data: &[u8]) {
+		tiny_keccak::Hasher::update(self, data);
+	}
+}
+impl hash::Finalize for Keccak {
+	type Output = Hash;
+
+	fn finalize(self) -> Self::Output {
+		let mut output = [0; 32];
+
+		tiny_keccak::Hasher::finalize(self, &mut output);
+
+		output
+	}
+}
 impl Merge for Keccak256 {
 	type Item = Hash;
 
+	fn empty() -> Self::Item {
+		[0; 32]
+	}
+
 	fn merge(l: &Self::Item, r: &Self::Item) -> Self::Item {
-		let mut m = [0u8; 64];
+		let mut output = [0u8; 32];
+
+		Self::merge_into(&mut output, l, r);
+
+		output
+	}
 
-		m[..32].copy_from_slice(l.as_ref());
-		m[32..].copy_from_slice(r.as_ref());
+	fn merge_into(out: &mut Self::Item, l: &Self::Item, r: &Self::Item) {
+		let mut keccak = Keccak::v256();
 
-		Keccak256::hash(&m)
+		keccak.update(l);
+		keccak.update(r);
+		keccak.finalize(out);
+	}
+}
+impl MergeCount for Keccak256 {
+	/// `keccak256(abi.encodePacked(uint32(non_empty_leaves_count)))`
+	fn count(non_empty_leaves_count: u32) -> Self::Item {
+		Keccak256::hash(non_empty_leaves_count.to_be_bytes())
 	}
 }
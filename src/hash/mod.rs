@@ -6,6 +6,26 @@ pub trait Hasher {
 		T: AsRef<[u8]>;
 }
 
+pub trait Update {
+	fn update(&mut self, data: &[u8]);
+}
+
+pub trait Finalize {
+	type Output;
+
+	fn finalize(self) -> Self::Output;
+}
+
+/// Hash the data piece by piece, without buffering it in one contiguous slice.
+///
+/// Feeding the pieces of `data` into [`IncrementalHasher::hasher`] must produce the same hash as
+/// [`Hasher::hash`] of `data`.
+pub trait IncrementalHasher: Hasher {
+	type State: Update + Finalize<Output = Self::Hash>;
+
+	fn hasher() -> Self::State;
+}
+
 #[cfg(feature = "keccak")]
 pub mod keccak;
 #[cfg(feature = "keccak")]
@@ -6,17 +6,50 @@ pub struct DebugView;
 impl Merge for DebugView {
 	type Item = u32;
 
+	fn empty() -> Self::Item {
+		0
+	}
+
 	fn merge(l: &Self::Item, r: &Self::Item) -> Self::Item {
 		*l + *r
 	}
 }
 
+impl MergeCount for DebugView {
+	fn count(non_empty_leaves_count: u32) -> Self::Item {
+		non_empty_leaves_count
+	}
+}
+
 /// Require the merge order to be the same as which we used in the build.
 pub struct CheckMergeOrder;
 impl Merge for CheckMergeOrder {
 	type Item = u32;
 
+	fn empty() -> Self::Item {
+		0
+	}
+
 	fn merge(l: &Self::Item, r: &Self::Item) -> Self::Item {
 		2 * *l + *r
 	}
 }
+
+/// Byte array flavor of [`DebugView`], for the byte-oriented APIs.
+pub struct DebugBytes;
+impl Merge for DebugBytes {
+	type Item = [u8; 4];
+
+	fn empty() -> Self::Item {
+		[0; 4]
+	}
+
+	fn merge(l: &Self::Item, r: &Self::Item) -> Self::Item {
+		u32::to_be_bytes(u32::from_be_bytes(*l).wrapping_add(u32::from_be_bytes(*r)))
+	}
+}
+impl MergeCount for DebugBytes {
+	fn count(non_empty_leaves_count: u32) -> Self::Item {
+		non_empty_leaves_count.to_be_bytes()
+	}
+}
@@ -0,0 +1,129 @@
+//! Keep appending the leaves without storing them.
+
+// --- core ---
+use core::marker::PhantomData;
+// --- alloc ---
+use alloc::vec::Vec;
+// --- sparse-merkle-tree ---
+use crate::*;
+
+/// The root of the leaves appended so far, same as [`SparseMerkleTree::root`].
+///
+/// Like the Ethereum deposit contract, only the roots of the complete left subtrees are kept, one
+/// per height at most. So it takes `O(log n)` space, and `O(1)` amortized merges per leaf.
+/// No proof can be made from it.
+pub struct IncrementalRoot<H, M> {
+	/// `frontier[h]` is the root of the complete subtree of height `h`, if the `h`th bit of the
+	/// leaves count is set. Otherwise, it's stale.
+	frontier: Vec<H>,
+	non_empty_leaves_count: u32,
+	_merge: PhantomData<M>,
+}
+impl<H, M> Clone for IncrementalRoot<H, M>
+where
+	H: Clone,
+{
+	fn clone(&self) -> Self {
+		Self {
+			frontier: self.frontier.clone(),
+			non_empty_leaves_count: self.non_empty_leaves_count,
+			_merge: PhantomData,
+		}
+	}
+}
+impl<H, M> Default for IncrementalRoot<H, M> {
+	fn default() -> Self {
+		Self {
+			frontier: Vec::new(),
+			non_empty_leaves_count: 0,
+			_merge: PhantomData,
+		}
+	}
+}
+impl<H, M> IncrementalRoot<H, M>
+where
+	H: Clone + MaybeDebug + PartialEq,
+	M: Merge<Item = H>,
+{
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Restore from the stored [`IncrementalRoot::frontier`], e.g. in a runtime.
+	///
+	/// `None` if the frontier length doesn't match the leaves count.
+	pub fn from_frontier(frontier: Vec<H>, non_empty_leaves_count: u32) -> Option<Self> {
+		if frontier.len() as u32 != u32::BITS - non_empty_leaves_count.leading_zeros() {
+			return None;
+		}
+
+		Some(Self {
+			frontier,
+			non_empty_leaves_count,
+			_merge: PhantomData,
+		})
+	}
+
+	/// One node per height, up to the highest bit of the leaves count.
+	pub fn frontier(&self) -> &[H] {
+		&self.frontier
+	}
+
+	/// Append a leaf and return its index.
+	pub fn push(&mut self, leaf: H) -> u32 {
+		let index = self.non_empty_leaves_count;
+		let mut node = leaf;
+		let mut height = 0;
+
+		// Merge the complete subtrees on the left, carry as the binary addition.
+		while index >> height & 1 == 1 {
+			node = M::merge(&self.frontier[height], &node);
+			height += 1;
+		}
+
+		if height == self.frontier.len() {
+			self.frontier.push(node);
+		} else {
+			self.frontier[height] = node;
+		}
+
+		self.non_empty_leaves_count += 1;
+
+		index
+	}
+
+	pub fn root(&self) -> H {
+		let non_empty_leaves_count = self.non_empty_leaves_count;
+
+		if non_empty_leaves_count == 0 {
+			return M::empty();
+		}
+
+		let depth =
+			non_empty_to_half_leaves_count(non_empty_leaves_count).trailing_zeros() as usize;
+
+		// The tree is complete.
+		if non_empty_leaves_count == 1 << depth {
+			return self.frontier[depth].clone();
+		}
+
+		let mut node = M::empty();
+		let mut empty = M::empty();
+
+		for height in 0..depth {
+			if non_empty_leaves_count >> height & 1 == 1 {
+				node = M::merge(&self.frontier[height], &node);
+			} else {
+				node = M::merge(&node, &empty);
+			}
+
+			empty = M::merge(&empty, &empty);
+		}
+
+		node
+	}
+
+	pub fn non_empty_leaves_count(&self) -> u32 {
+		self.non_empty_leaves_count
+	}
+}
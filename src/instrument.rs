@@ -0,0 +1,90 @@
+//! Hooks to observe the work done by the tree operations.
+//!
+//! Useful to estimate the weights and to catch the performance regressions in the benchmarks.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operation {
+	New,
+	ProofOf,
+	Verify,
+}
+
+/// All the hooks are no-op by default, `()` ignores everything.
+///
+/// Note that [`SparseMerkleTree::proof_of`](crate::SparseMerkleTree::proof_of) only reads the
+/// tree, it never merges.
+pub trait Instrument {
+	/// Called before the operation starts.
+	///
+	/// Pair it with [`Instrument::exit`] to measure the timing.
+	fn enter(&mut self, _operation: Operation) {}
+
+	/// Called after the operation finishes.
+	fn exit(&mut self, _operation: Operation) {}
+
+	/// Called on every [`Merge::merge`](crate::Merge::merge) invocation.
+	fn on_merge(&mut self, _operation: Operation) {}
+
+	/// Called before every merge of the verification, return `false` to abort it.
+	fn should_merge(&mut self, _operation: Operation) -> bool {
+		true
+	}
+}
+impl Instrument for () {}
+
+/// Count the merge invocations per operation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MergeCounter {
+	pub new: u32,
+	pub verify: u32,
+}
+impl Instrument for MergeCounter {
+	fn on_merge(&mut self, operation: Operation) {
+		match operation {
+			Operation::New => self.new += 1,
+			Operation::ProofOf => (),
+			Operation::Verify => self.verify += 1,
+		}
+	}
+}
+
+/// Abort the verification once `max_merges` merges are done.
+///
+/// See [`SparseMerkleTree::verify_with_budget`](crate::SparseMerkleTree::verify_with_budget).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MergeBudget {
+	remaining: u32,
+	exceeded: bool,
+}
+impl MergeBudget {
+	pub fn new(max_merges: u32) -> Self {
+		Self {
+			remaining: max_merges,
+			exceeded: false,
+		}
+	}
+
+	pub fn remaining(&self) -> u32 {
+		self.remaining
+	}
+
+	/// Whether a verification was aborted.
+	pub fn exceeded(&self) -> bool {
+		self.exceeded
+	}
+}
+impl Instrument for MergeBudget {
+	fn on_merge(&mut self, operation: Operation) {
+		if operation == Operation::Verify {
+			self.remaining = self.remaining.saturating_sub(1);
+		}
+	}
+
+	fn should_merge(&mut self, operation: Operation) -> bool {
+		if operation == Operation::Verify && self.remaining == 0 {
+			self.exceeded = true;
+		}
+
+		!self.exceeded
+	}
+}
@@ -0,0 +1,265 @@
+//! Trees with `ARITY` children per node, e.g. for the zk circuits with a wide hash.
+//!
+//! The binary [`SparseMerkleTree`] stays the default, [`Binary`] turns a [`Merge`] into a
+//! [`MergeN`] with the same root.
+
+// --- core ---
+use core::marker::PhantomData;
+// --- alloc ---
+use alloc::vec::Vec;
+// --- sparse-merkle-tree ---
+use crate::*;
+
+pub trait MergeN {
+	type Item;
+
+	/// The empty leaf, which is used to pad the tree.
+	fn empty() -> Self::Item;
+
+	/// Always called with exactly `ARITY` children, from the left to the right.
+	fn merge_n(children: &[Self::Item]) -> Self::Item;
+}
+
+/// The binary [`Merge`] as a [`MergeN`], use it with `ARITY = 2`.
+pub struct Binary<M>(PhantomData<M>);
+impl<M> MergeN for Binary<M>
+where
+	M: Merge,
+{
+	type Item = M::Item;
+
+	fn empty() -> Self::Item {
+		M::empty()
+	}
+
+	fn merge_n(children: &[Self::Item]) -> Self::Item {
+		M::merge(&children[0], &children[1])
+	}
+}
+
+/// The nodes are stored level by level, `levels[0]` is the leaves, the last level is the root.
+pub struct KaryTree<H, M, const ARITY: usize> {
+	levels: Vec<Vec<H>>,
+	non_empty_leaves_count: u32,
+	_merge: PhantomData<M>,
+}
+impl<H, M, const ARITY: usize> KaryTree<H, M, ARITY>
+where
+	H: Clone + MaybeDebug + PartialEq,
+	M: MergeN<Item = H>,
+{
+	/// Panic if `ARITY < 2`.
+	pub fn new<L>(leaves: L) -> Self
+	where
+		L: IntoIterator<Item = H>,
+	{
+		assert!(ARITY >= 2, "the arity must be at least 2");
+
+		let mut leaves = leaves.into_iter().collect::<Vec<_>>();
+		let non_empty_leaves_count = leaves.len() as u32;
+		let capacity = capacity::<ARITY>(leaves.len());
+
+		leaves.resize(capacity, M::empty());
+
+		let mut levels = Vec::with_capacity(depth::<ARITY>(capacity) as usize + 1);
+
+		levels.push(leaves);
+
+		while levels[levels.len() - 1].len() > 1 {
+			let parents = levels[levels.len() - 1]
+				.chunks(ARITY)
+				.map(M::merge_n)
+				.collect();
+
+			levels.push(parents);
+		}
+
+		Self {
+			levels,
+			non_empty_leaves_count,
+			_merge: PhantomData,
+		}
+	}
+
+	pub fn root(&self) -> H {
+		self.levels[self.levels.len() - 1][0].clone()
+	}
+
+	pub fn depth(&self) -> u32 {
+		self.levels.len() as u32 - 1
+	}
+
+	pub fn non_empty_leaves_count(&self) -> u32 {
+		self.non_empty_leaves_count
+	}
+
+	/// The leaves could be in any order, out of bounds or duplicate indices return `None`.
+	pub fn proof_of<I>(&self, indices: I) -> Option<KaryProof<H, M, ARITY>>
+	where
+		I: IntoIterator,
+		I::Item: Borrow<u32>,
+	{
+		let indices = collect_indices(indices);
+		let mut known = indices.clone();
+
+		known.sort_unstable();
+
+		if known.windows(2).any(|w| w[0] == w[1])
+			|| known
+				.last()
+				.is_some_and(|i| *i >= self.non_empty_leaves_count)
+		{
+			return None;
+		}
+
+		let leaves_with_index = indices
+			.iter()
+			.map(|i| (*i, self.levels[0][*i as usize].clone()))
+			.collect();
+		let mut siblings = Vec::new();
+
+		for level in &self.levels[..self.levels.len() - 1] {
+			let mut parents = Vec::<u32>::with_capacity(known.len());
+
+			for i in &known {
+				let parent = i / ARITY as u32;
+
+				if parents.last() == Some(&parent) {
+					continue;
+				}
+
+				parents.push(parent);
+
+				let first = parent as usize * ARITY;
+				let last = first.checked_add(ARITY)?;
+
+				(first..last)
+					.filter(|j| known.binary_search(&(*j as u32)).is_err())
+					.for_each(|j| siblings.push(level[j].clone()));
+			}
+
+			known = parents;
+		}
+
+		Some(KaryProof {
+			root: self.root(),
+			depth: self.depth(),
+			leaves_with_index,
+			siblings,
+			_merge: PhantomData,
+		})
+	}
+
+	pub fn verify(proof: &KaryProof<H, M, ARITY>) -> bool {
+		proof.compute_root().as_ref() == Some(&proof.root)
+	}
+}
+
+pub struct KaryProof<H, M, const ARITY: usize> {
+	pub root: H,
+	pub depth: u32,
+	/// `(leaf index, leaf)`
+	pub leaves_with_index: Vec<(u32, H)>,
+	/// Level by level from the bottom, the groups from the left, the children from the left.
+	pub siblings: Vec<H>,
+	_merge: PhantomData<M>,
+}
+// Implement this manually, the derived one would require `M` to implement it too.
+impl<H, M, const ARITY: usize> Clone for KaryProof<H, M, ARITY>
+where
+	H: Clone,
+{
+	fn clone(&self) -> Self {
+		Self {
+			root: self.root.clone(),
+			depth: self.depth,
+			leaves_with_index: self.leaves_with_index.clone(),
+			siblings: self.siblings.clone(),
+			_merge: PhantomData,
+		}
+	}
+}
+impl<H, M, const ARITY: usize> KaryProof<H, M, ARITY>
+where
+	H: Clone + MaybeDebug + PartialEq,
+	M: MergeN<Item = H>,
+{
+	/// `None` if the proof is malformed, e.g. missing siblings or leaves out of the depth.
+	fn compute_root(&self) -> Option<H> {
+		if ARITY < 2 || self.leaves_with_index.is_empty() {
+			return None;
+		}
+
+		let arity = u32::try_from(ARITY).ok()?;
+		let capacity = (ARITY as u64).checked_pow(self.depth)?;
+		let mut known = self.leaves_with_index.clone();
+
+		known.sort_unstable_by_key(|(i, _)| *i);
+
+		if known.windows(2).any(|w| w[0].0 == w[1].0)
+			|| known.last().is_some_and(|(i, _)| *i as u64 >= capacity)
+		{
+			return None;
+		}
+
+		let mut siblings = self.siblings.iter();
+		let mut children = Vec::with_capacity(ARITY);
+
+		for _ in 0..self.depth {
+			let mut parents = Vec::<(u32, H)>::with_capacity(known.len());
+			let mut k = 0;
+
+			while k < known.len() {
+				let parent = known[k].0 / arity;
+				let first = parent * arity;
+				// The group of the indices near `u32::MAX` is out of the `u32` range.
+				let last = first.checked_add(arity)?;
+
+				children.clear();
+
+				for j in first..last {
+					match known.get(k) {
+						Some((i, node)) if *i == j => {
+							children.push(node.clone());
+							k += 1;
+						}
+						_ => children.push(siblings.next()?.clone()),
+					}
+				}
+
+				parents.push((parent, M::merge_n(&children)));
+			}
+
+			known = parents;
+		}
+
+		// All the siblings must be consumed.
+		if siblings.next().is_some() {
+			return None;
+		}
+
+		known.pop().map(|(_, root)| root)
+	}
+}
+
+fn capacity<const ARITY: usize>(leaves_count: usize) -> usize {
+	let mut capacity = 1;
+
+	while capacity < leaves_count {
+		capacity *= ARITY;
+	}
+
+	capacity
+}
+
+fn depth<const ARITY: usize>(capacity: usize) -> u32 {
+	let mut depth = 0;
+	let mut c = 1;
+
+	while c < capacity {
+		c *= ARITY;
+		depth += 1;
+	}
+
+	depth
+}
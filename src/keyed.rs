@@ -0,0 +1,74 @@
+//! Assign the leaf indices to arbitrary keys, e.g. the account ids of an airdrop, and look them up.
+
+// --- alloc ---
+use alloc::{collections::BTreeMap, vec::Vec};
+// --- sparse-merkle-tree ---
+use crate::{hash::Hasher, *};
+
+/// The leaf index of each key.
+pub type KeyIndices<K> = BTreeMap<K, u32>;
+
+impl<H, M> SparseMerkleTree<H, M>
+where
+	H: Clone + MaybeDebug + Ord,
+	M: Merge<Item = H> + Hasher<Hash = H>,
+{
+	/// Build the tree from the `(key, leaf)` pairs, ordered by `M::hash(key)`.
+	///
+	/// The order only depends on the keys, not on the input order, so every party assigns the same
+	/// index to the same key. The equal hashes, which shouldn't happen, are ordered by the keys.
+	///
+	/// A key appearing more than once is only assigned once, the first leaf wins.
+	pub fn new_keyed<K, L>(entries: L) -> (Self, KeyIndices<K>)
+	where
+		K: AsRef<[u8]> + Ord,
+		L: IntoIterator<Item = (K, H)>,
+	{
+		let mut entries = entries
+			.into_iter()
+			.map(|(key, leaf)| (M::hash(&key), key, leaf))
+			.collect::<Vec<_>>();
+
+		// Stable, the first leaf of a duplicate key stays in front.
+		entries.sort_by(|(a_hash, a, _), (b_hash, b, _)| (a_hash, a).cmp(&(b_hash, b)));
+		entries.dedup_by(|(_, b, _), (_, a, _)| a == b);
+
+		let mut key_indices = BTreeMap::new();
+		let leaves = entries
+			.into_iter()
+			.enumerate()
+			.map(|(i, (_, key, leaf))| {
+				key_indices.insert(key, i as u32);
+
+				leaf
+			})
+			.collect::<Vec<_>>();
+
+		(Self::new(leaves), key_indices)
+	}
+}
+
+/// The index of each leaf of a [`SparseMerkleTree`], for the repeated
+/// [`SparseMerkleTree::contains_hash`] lookups in `O(log n)`.
+///
+/// It's a snapshot, rebuild it after the tree is updated.
+pub struct LeafIndex<H>(BTreeMap<H, u32>);
+impl<H> LeafIndex<H>
+where
+	H: Clone + MaybeDebug + Ord,
+{
+	/// The first index is kept if a leaf appears more than once.
+	pub fn new<M>(tree: &SparseMerkleTree<H, M>) -> Self {
+		let mut leaf_indices = BTreeMap::new();
+
+		tree.leaves().iter().enumerate().for_each(|(i, leaf)| {
+			leaf_indices.entry(leaf.clone()).or_insert(i as u32);
+		});
+
+		Self(leaf_indices)
+	}
+
+	pub fn get(&self, leaf: &H) -> Option<u32> {
+		self.0.get(leaf).copied()
+	}
+}
@@ -1,23 +1,151 @@
 #![no_std]
 
 extern crate alloc;
+#[cfg(any(feature = "python", feature = "threads", test))]
+extern crate std;
 
-#[cfg(any(test, feature = "keccak"))]
+/// Forward to `tracing` if the `tracing` feature is enabled, otherwise to `log`.
+///
+/// Everything is logged under the [`LOG_TARGET`], nothing is logged with the `quiet` feature.
+#[cfg(feature = "debug")]
+macro_rules! trace {
+	($($arg:tt)*) => {
+		#[cfg(all(feature = "tracing", not(feature = "quiet")))]
+		tracing::trace!(target: crate::LOG_TARGET, $($arg)*);
+		#[cfg(all(not(feature = "tracing"), not(feature = "quiet")))]
+		log::trace!(target: crate::LOG_TARGET, $($arg)*);
+	};
+}
+#[cfg(feature = "debug")]
+macro_rules! debug {
+	($($arg:tt)*) => {
+		#[cfg(all(feature = "tracing", not(feature = "quiet")))]
+		tracing::debug!(target: crate::LOG_TARGET, $($arg)*);
+		#[cfg(all(not(feature = "tracing"), not(feature = "quiet")))]
+		log::debug!(target: crate::LOG_TARGET, $($arg)*);
+	};
+}
+macro_rules! warn {
+	($($arg:tt)*) => {
+		#[cfg(all(feature = "tracing", not(feature = "quiet")))]
+		tracing::warn!(target: crate::LOG_TARGET, $($arg)*);
+		#[cfg(all(not(feature = "tracing"), not(feature = "quiet")))]
+		log::warn!(target: crate::LOG_TARGET, $($arg)*);
+	};
+}
+
+pub mod audit;
+#[cfg(feature = "runtime-benchmarks")]
+pub mod benchmarking;
+pub mod codec;
+pub mod commitment;
+pub mod compact_tree;
+pub mod compressed;
+mod const_root;
+pub mod dyn_merge;
+pub mod foreign;
+pub mod forest;
 pub mod hash;
+pub mod incremental;
+pub mod instrument;
+pub mod kary;
+pub mod keyed;
+pub mod linkage;
+#[cfg(any(feature = "parallel", feature = "threads"))]
+mod parallel;
+pub mod proof_cache;
+#[cfg(feature = "python")]
+mod python;
+pub mod shared;
+#[cfg(feature = "solidity-codegen")]
+pub mod solidity;
+pub mod stats;
+#[cfg(feature = "proptest")]
+pub mod strategies;
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
+#[cfg(feature = "test-vector")]
+pub mod test_vector;
 #[cfg(test)]
 mod tests;
+pub mod virtual_tree;
 
 // --- core ---
-use core::fmt::Debug;
+use core::{
+	borrow::Borrow,
+	cmp::Ordering,
+	fmt::Debug,
+	hash::{Hash, Hasher},
+	marker::PhantomData,
+};
 // --- alloc ---
-use alloc::vec::Vec;
+use alloc::{collections::VecDeque, vec, vec::Vec};
+// --- sparse-merkle-tree ---
+use audit::SharedAuditLog;
+use dyn_merge::{DynMerge, MergeAdapter};
+use hash::IncrementalHasher;
+use instrument::{Instrument, MergeBudget, Operation};
+
+/// The target of all the logs of this crate.
+pub const LOG_TARGET: &str = "tiny-smt";
+
+/// The deepest supported tree.
+///
+/// The node indices of a deeper tree would still fit in `u32`, but the `u32` arithmetic on them
+/// wouldn't, e.g. the nodes count `half_leaves_count * 2` and the end of a level `2 << level` are
+/// `2^32` in a tree of depth `31`.
+pub const MAX_DEPTH: u32 = u32::BITS - 2;
 
 pub trait Merge {
 	type Item;
 
+	/// The empty leaf, which is used to pad the tree.
+	fn empty() -> Self::Item;
+
 	fn merge(l: &Self::Item, r: &Self::Item) -> Self::Item;
+
+	/// Write the merge result into an existing node.
+	///
+	/// Override it if the item is large, to avoid creating a fresh one on every merge.
+	fn merge_into(out: &mut Self::Item, l: &Self::Item, r: &Self::Item) {
+		*out = Self::merge(l, r);
+	}
+}
+
+/// How to fill the leaves after the non-empty ones, up to the power of two.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Padding {
+	/// Pad with [`Merge::empty`].
+	#[default]
+	Empty,
+	/// Bitcoin style, duplicate the last node on every level.
+	///
+	/// Pad with [`Merge::empty`] if there isn't any non-empty leaf.
+	DuplicateLast,
+}
+
+/// Commit the leaves count into the root, see [`SparseMerkleTree::committed_root`].
+///
+/// Otherwise, trees which only differ in the trailing empty leaves, have the same root.
+pub trait MergeCount: Merge {
+	fn count(non_empty_leaves_count: u32) -> Self::Item;
 }
 
+/// [`Debug`] if the `debug` feature is enabled, which logs the nodes, otherwise nothing.
+///
+/// So the hash types don't have to implement [`Debug`] only for this crate.
+#[cfg(feature = "debug")]
+pub trait MaybeDebug: Debug {}
+#[cfg(feature = "debug")]
+impl<T> MaybeDebug for T where T: Debug {}
+/// [`Debug`] if the `debug` feature is enabled, which logs the nodes, otherwise nothing.
+///
+/// So the hash types don't have to implement [`Debug`] only for this crate.
+#[cfg(not(feature = "debug"))]
+pub trait MaybeDebug {}
+#[cfg(not(feature = "debug"))]
+impl<T> MaybeDebug for T {}
+
 /// > Assume the hash algorithm is `a + b`.
 ///
 /// ## Tree
@@ -35,73 +163,361 @@ pub trait Merge {
 /// [0,0,1+2,3+4,1,2,3,4]
 /// [0,1+2+3+4,1+2,3+4,1,2,3,4]
 /// ```
-#[cfg_attr(all(feature = "debug", not(test)), derive(Debug))]
-pub struct SparseMerkleTree<H> {
-	pub nodes: Vec<H>,
-	pub non_empty_leaves_count: u32,
+///
+/// The merger `M` is a part of the type, so a tree or a proof can't be verified with another merger.
+pub struct SparseMerkleTree<H, M> {
+	nodes: Vec<H>,
+	non_empty_leaves_count: u32,
+	audit_log: Option<SharedAuditLog<H, M>>,
+	_merge: PhantomData<M>,
+}
+impl<H, M> Debug for SparseMerkleTree<H, M>
+where
+	H: Debug,
+{
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		f.debug_struct("SparseMerkleTree")
+			.field("nodes", &self.nodes)
+			.field("non_empty_leaves_count", &self.non_empty_leaves_count)
+			.finish()
+	}
 }
-impl<H> SparseMerkleTree<H>
+impl<H, M> SparseMerkleTree<H, M>
 where
-	H: Clone + Debug + Default + PartialEq,
+	H: Clone + MaybeDebug + PartialEq,
+	M: Merge<Item = H>,
 {
-	pub fn new<L, M>(leaves: L) -> Self
+	pub fn new<L>(leaves: L) -> Self
 	where
-		L: Iterator<Item = H>,
-		M: Merge<Item = H>,
+		L: IntoIterator<Item = H>,
 	{
-		let non_empty_leaves_count = leaves.size_hint().0 as u32;
-		let half_leaves_count = non_empty_to_half_leaves_count(non_empty_leaves_count);
-		let leaves_count = half_leaves_count * 2;
-		let mut nodes = Vec::with_capacity(leaves_count as _);
+		Self::new_with_instrument(leaves, &mut ())
+	}
 
-		#[cfg(feature = "debug")]
-		{
-			log::debug!("new::non_empty_leaves_count: {}", non_empty_leaves_count);
-			log::debug!("new::half_leaves_count: {}", half_leaves_count);
+	pub fn new_with_instrument<L, I>(leaves: L, instrument: &mut I) -> Self
+	where
+		L: IntoIterator<Item = H>,
+		I: Instrument,
+	{
+		Self::build(
+			&MergeAdapter::<M>::new(),
+			leaves,
+			Padding::Empty,
+			None,
+			None,
+			instrument,
+		)
+	}
+
+	pub fn new_with_padding<L>(leaves: L, padding: Padding) -> Self
+	where
+		L: IntoIterator<Item = H>,
+	{
+		Self::build(
+			&MergeAdapter::<M>::new(),
+			leaves,
+			padding,
+			None,
+			None,
+			&mut (),
+		)
+	}
+
+	/// Hash each leaf from its data pieces, e.g. the chunks of a large payload streamed from disk.
+	pub fn new_from_data<L, D>(leaves: L) -> Self
+	where
+		L: Iterator<Item = D>,
+		D: IntoIterator,
+		D::Item: AsRef<[u8]>,
+		M: IncrementalHasher<Hash = H>,
+	{
+		Self::new(leaves.map(|data| {
+			let mut hasher = M::hasher();
+
+			data.into_iter()
+				.for_each(|piece| hash::Update::update(&mut hasher, piece.as_ref()));
+
+			hash::Finalize::finalize(hasher)
+		}))
+	}
+
+	/// Restore a tree from its [`SparseMerkleTree::into_nodes`].
+	///
+	/// Every node is checked, including the padding ones. The padding must be either
+	/// [`Padding::Empty`], whose padding leaves are [`Merge::empty`], or [`Padding::DuplicateLast`].
+	/// Otherwise, the error of [`Padding::Empty`] is returned.
+	pub fn from_nodes(nodes: Vec<H>, non_empty_leaves_count: u32) -> Result<Self, TreeError> {
+		if non_empty_leaves_count == 0 && nodes.is_empty() {
+			return Ok(Self::empty());
 		}
 
-		// Fill the empty leaves.
-		(0..half_leaves_count).for_each(|_| nodes.push(Default::default()));
-		// Fill the leaves.
-		leaves.for_each(|leaf| nodes.push(leaf));
-		// Fill the empty leaves.
-		// `x.next_power_of_two()` must grater/equal than/to `x`; qed
-		(0..half_leaves_count - non_empty_leaves_count)
-			.for_each(|_| nodes.push(Default::default()));
-		// Build the SMT.
-		(1..half_leaves_count).rev().for_each(|i| {
-			let i = i as usize;
-			let l = &nodes[i * 2];
-			let r = &nodes[i * 2 + 1];
-
-			nodes[i] = M::merge(l, r);
-		});
+		let half_leaves_count = checked_half_leaves_count(non_empty_leaves_count)
+			.ok_or(TreeError::InvalidNodesCount)?;
+
+		if nodes.len() != half_leaves_count as usize * 2 {
+			return Err(TreeError::InvalidNodesCount);
+		}
+
+		let depth = half_leaves_count.trailing_zeros();
+		let check = |padding| {
+			for height in 0..=depth {
+				let level = depth - height;
+
+				for i in (1_u32 << level)..(2 << level) {
+					let is_padding = (i << height) - half_leaves_count >= non_empty_leaves_count;
+					let i = i as usize;
+					let expected = match (is_padding, padding) {
+						(false, _) if height == 0 => continue,
+						// Not the leftmost node, since there is at least one non-empty leaf.
+						(true, Padding::DuplicateLast) => nodes[i - 1].clone(),
+						(true, Padding::Empty) if height == 0 => M::empty(),
+						_ => M::merge(&nodes[i * 2], &nodes[i * 2 + 1]),
+					};
+
+					if nodes[i] != expected {
+						return Err(TreeError::InconsistentNode(i as _));
+					}
+				}
+			}
+
+			Ok(())
+		};
+
+		check(Padding::Empty).or_else(|e| {
+			if non_empty_leaves_count != 0 && check(Padding::DuplicateLast).is_ok() {
+				Ok(())
+			} else {
+				Err(e)
+			}
+		})?;
+
+		Ok(Self {
+			nodes,
+			non_empty_leaves_count,
+			audit_log: None,
+			_merge: PhantomData,
+		})
+	}
+
+	/// Restore a tree from a trusted snapshot in `O(1)`, without any check.
+	///
+	/// The caller must guarantee that `nodes` and `non_empty_leaves_count` were taken from a tree
+	/// built with the same `M`, and weren't modified since then.
+	/// Otherwise, the root and the proofs are meaningless, and the other methods may panic.
+	/// This won't cause any undefined behavior though.
+	pub fn from_nodes_unchecked(nodes: Vec<H>, non_empty_leaves_count: u32) -> Self {
+		debug_assert!(
+			(non_empty_leaves_count == 0 && nodes.is_empty())
+				|| nodes.len()
+					== non_empty_to_half_leaves_count(non_empty_leaves_count) as usize * 2
+		);
 
 		Self {
 			nodes,
 			non_empty_leaves_count,
+			audit_log: None,
+			_merge: PhantomData,
 		}
 	}
 
-	pub fn leaves_count(&self) -> u32 {
-		self.nodes.len() as _
+	/// Take the empty subtrees from `default_nodes` instead of merging them.
+	pub fn new_with_default_nodes<L>(leaves: L, default_nodes: &DefaultNodes<H, M>) -> Self
+	where
+		L: IntoIterator<Item = H>,
+	{
+		Self::build(
+			&MergeAdapter::<M>::new(),
+			leaves,
+			Padding::Empty,
+			None,
+			Some(default_nodes),
+			&mut (),
+		)
 	}
 
-	#[cfg(test)]
-	pub fn half_leaves_count(&self) -> u32 {
-		self.leaves_count() / 2
+	/// Same as [`SparseMerkleTree::new`], but the tree must not be deeper than `max_depth`.
+	///
+	/// So no proof takes more than `max_depth` siblings per leaf, e.g. for the contracts which
+	/// verify with a fixed-size loop.
+	pub fn new_with_max_depth<L>(leaves: L, max_depth: u32) -> Result<Self, TreeError>
+	where
+		L: IntoIterator<Item = H>,
+	{
+		let leaves = leaves.into_iter().collect::<Vec<_>>();
+
+		if max_depth > MAX_DEPTH {
+			return Err(TreeError::InvalidDepth);
+		}
+		if leaves.len() > 1 << max_depth {
+			return Err(TreeError::TooManyLeaves);
+		}
+
+		Ok(Self::new(leaves))
 	}
 
-	pub fn non_empty_leaves_count(&self) -> u32 {
-		self.non_empty_leaves_count
+	/// Pad the tree to `depth` regardless of the leaves count, so the root and the proof size
+	/// stay stable as the leaves are added over time.
+	///
+	/// The empty subtrees are taken from `default_nodes`.
+	/// Note that [`SparseMerkleTree::from_nodes`] and the committed root assume the minimal depth.
+	pub fn new_with_depth<L>(
+		leaves: L,
+		depth: u32,
+		default_nodes: &DefaultNodes<H, M>,
+	) -> Result<Self, TreeError>
+	where
+		L: IntoIterator<Item = H>,
+	{
+		let leaves = leaves.into_iter().collect::<Vec<_>>();
+
+		if depth > MAX_DEPTH {
+			return Err(TreeError::InvalidDepth);
+		}
+		if leaves.len() > 1 << depth {
+			return Err(TreeError::TooManyLeaves);
+		}
+
+		Ok(Self::build(
+			&MergeAdapter::<M>::new(),
+			leaves,
+			Padding::Empty,
+			Some(depth),
+			Some(default_nodes),
+			&mut (),
+		))
 	}
 
+	/// [`Merge::empty`] if the tree [`is_empty`](SparseMerkleTree::is_empty).
 	pub fn root(&self) -> H {
-		if self.leaves_count() == 0 {
-			Default::default()
+		self.root_inner(&MergeAdapter::<M>::new())
+	}
+
+	/// The root without the padding-only subtrees, a node whose right subtree is padding-only is
+	/// replaced by its left child.
+	///
+	/// So the trees with the same non-empty leaves always agree, regardless of the capacity and
+	/// the [`Padding`]. Only the nodes on the right edge of the non-empty leaves are re-merged.
+	pub fn canonical_root(&self) -> H {
+		if self.non_empty_leaves_count == 0 {
+			return M::empty();
+		}
+
+		self.canonical_node(1, self.depth())
+	}
+
+	fn canonical_node(&self, i: u32, height: u32) -> H {
+		let first_leaf = (i << height) - self.capacity();
+
+		// All the leaves are non-empty.
+		if first_leaf + (1 << height) <= self.non_empty_leaves_count {
+			return self.nodes[i as usize].clone();
+		}
+
+		let l = self.canonical_node(i * 2, height - 1);
+
+		// The right subtree is padding-only.
+		if first_leaf + (1 << (height - 1)) >= self.non_empty_leaves_count {
+			l
 		} else {
-			self.nodes[1].clone()
+			M::merge(&l, &self.canonical_node(i * 2 + 1, height - 1))
+		}
+	}
+
+	/// Replace the leaf at `index`, and re-merge its ancestors.
+	///
+	/// Only for the trees padded with [`Padding::Empty`], the duplicated padding leaves of
+	/// [`Padding::DuplicateLast`] are not updated.
+	pub fn update_leaf(&mut self, index: u32, leaf: H) -> Result<(), TreeError> {
+		let root_before = self.audit_root();
+
+		self.update_leaf_inner(index, leaf)?;
+		self.audit_update(&[index], root_before);
+
+		Ok(())
+	}
+
+	fn update_leaf_inner(&mut self, index: u32, leaf: H) -> Result<(), TreeError> {
+		if index >= self.non_empty_leaves_count {
+			return Err(TreeError::IndexOutOfBounds(index));
+		}
+
+		let mut i = (self.capacity() + index) as usize;
+
+		self.nodes[i] = leaf;
+
+		while i > 1 {
+			i /= 2;
+
+			let (parents, children) = self.nodes.split_at_mut(i * 2);
+
+			M::merge_into(&mut parents[i], &children[0], &children[1]);
+		}
+
+		Ok(())
+	}
+
+	/// Append a leaf and return its index.
+	///
+	/// Take the first padding leaf if there is any, otherwise rebuild the tree with the doubled
+	/// capacity. Same as [`SparseMerkleTree::update_leaf`], only for [`Padding::Empty`].
+	pub fn push_leaf(&mut self, leaf: H) -> u32 {
+		let root_before = self.audit_root();
+		let index = self.non_empty_leaves_count;
+		let capacity = self.capacity();
+
+		if index == capacity {
+			let leaves = core::mem::take(&mut self.nodes)
+				.into_iter()
+				.skip(capacity as _)
+				.take(index as _)
+				.chain(core::iter::once(leaf));
+			let audit_log = self.audit_log.take();
+
+			*self = Self::new(leaves);
+			self.audit_log = audit_log;
+		} else {
+			self.non_empty_leaves_count += 1;
+			self.update_leaf_inner(index, leaf)
+				.expect("`index` < `non_empty_leaves_count`; qed");
+		}
+
+		self.audit_update(&[index], root_before);
+
+		index
+	}
+
+	/// Find the leftmost deepest node which differs from `other`'s.
+	///
+	/// Return `(level, offset)`, the root is at level `0`, the leaves are at the last level.
+	/// Trees in different sizes always diverge at the root.
+	pub fn first_divergence(&self, other: &Self) -> Option<(u32, u32)> {
+		if self.nodes_count() != other.nodes_count() {
+			return Some((0, 0));
+		}
+		if self.root() == other.root() {
+			return None;
+		}
+
+		let leaves_count = self.nodes.len();
+		let mut i = 1;
+
+		// Walk down the differing path, prefer the left child.
+		while i * 2 < leaves_count {
+			let l = i * 2;
+			let r = l + 1;
+
+			if self.nodes[l] != other.nodes[l] {
+				i = l;
+			} else if self.nodes[r] != other.nodes[r] {
+				i = r;
+			} else {
+				break;
+			}
 		}
+
+		let level = usize::BITS - 1 - i.leading_zeros();
+
+		Some((level, (i - (1 << level)) as _))
 	}
 
 	/// ## Indices
@@ -109,143 +525,932 @@ where
 	// leaves  0 0 0 0 0 0 0 0 1 2 3 4 5 0 0 0
 	// indices                 0 1 2 3 4 5 6 7
 	/// ```
-	pub fn proof_of<I>(&self, indices: I) -> Proof<H>
+	pub fn proof_of<I>(&self, indices: I) -> Proof<H, M>
+	where
+		I: IntoIterator,
+		I::Item: Borrow<u32>,
+	{
+		self.proof_of_with_instrument(indices, &mut ())
+	}
+
+	pub fn proof_of_with_instrument<I, In>(&self, indices: I, instrument: &mut In) -> Proof<H, M>
+	where
+		I: IntoIterator,
+		I::Item: Borrow<u32>,
+		In: Instrument,
+	{
+		let indices = collect_indices(indices);
+		let proof = self.proof_of_traced(&indices, instrument, &mut ProofScratch::new());
+
+		self.audit_proof(&indices, &proof);
+
+		proof
+	}
+
+	/// Same as [`SparseMerkleTree::proof_of`], but reuse `scratch` across the calls.
+	///
+	/// Save the allocation per call, e.g. on the proof-heavy servers.
+	pub fn proof_of_with_scratch<I>(&self, indices: I, scratch: &mut ProofScratch) -> Proof<H, M>
+	where
+		I: IntoIterator,
+		I::Item: Borrow<u32>,
+	{
+		let indices = collect_indices(indices);
+		let proof = self.proof_of_traced(&indices, &mut (), scratch);
+
+		self.audit_proof(&indices, &proof);
+
+		proof
+	}
+
+	fn proof_of_traced<In>(
+		&self,
+		indices: &[u32],
+		instrument: &mut In,
+		scratch: &mut ProofScratch,
+	) -> Proof<H, M>
+	where
+		In: Instrument,
+	{
+		#[cfg(feature = "tracing")]
+		let _span = tracing::debug_span!(
+			target: LOG_TARGET,
+			"proof_of",
+			indices_count = indices.len(),
+			proof_size = tracing::field::Empty
+		)
+		.entered();
+
+		instrument.enter(Operation::ProofOf);
+
+		let proof = self.proof_of_inner(indices, &MergeAdapter::<M>::new(), scratch);
+
+		#[cfg(feature = "tracing")]
+		_span.record("proof_size", proof.proof.len());
+
+		instrument.exit(Operation::ProofOf);
+
+		proof
+	}
+
+	/// The single leaf proof of every non-empty leaf, in the index order.
+	///
+	/// Each proof is read straight from the leaf's path in `O(depth)`, instead of
+	/// [`SparseMerkleTree::proof_of`] which scans the whole tree every time.
+	pub fn proofs_for_all(&self) -> impl Iterator<Item = (u32, Proof<H, M>)> + '_ {
+		let root = self.root();
+		let capacity = self.capacity();
+
+		(0..self.non_empty_leaves_count).map(move |i| {
+			let leaf = capacity + i;
+			let mut proof = Vec::with_capacity(self.depth() as _);
+			let mut j = leaf;
+
+			while j > 1 {
+				proof.push(self.nodes[(j ^ 1) as usize].clone());
+
+				j /= 2;
+			}
+
+			let proof = Proof {
+				root: root.clone(),
+				leaves_with_index: [(leaf, self.nodes[leaf as usize].clone())].into(),
+				proof,
+				non_empty_leaves_count: None,
+				_merge: PhantomData,
+			};
+
+			self.audit_proof(&[i], &proof);
+
+			(i, proof)
+		})
+	}
+
+	/// Split the proof of `indices` into pages, each one takes at most `max_siblings_per_page`
+	/// siblings.
+	///
+	/// The split minimises the siblings in total, then the pages count. A single leaf takes
+	/// [`SparseMerkleTree::depth`] siblings, which might exceed the budget on its own. It takes
+	/// `O(n^2)` time of the indices count.
+	///
+	/// The pages are in the descending order, so are the leaves in each page.
+	pub fn proof_of_paged<I>(&self, indices: I, max_siblings_per_page: usize) -> Vec<Proof<H, M>>
 	where
-		I: AsRef<[u32]>,
+		I: IntoIterator,
+		I::Item: Borrow<u32>,
 	{
-		let indices = indices.as_ref();
-		let leaves_count = self.leaves_count();
-		let half_leaves_count = leaves_count / 2;
+		let mut indices = collect_indices(indices);
 
 		if indices.iter().any(|i| *i >= self.non_empty_leaves_count()) {
-			log::warn!("proof_of::Index out of bounds.");
+			warn!("proof_of_paged::Index out of bounds.");
 
-			return Default::default();
+			return Vec::new();
 		}
 
-		let mut known = Vec::with_capacity(leaves_count as _);
+		indices.sort_unstable_by(|a, b| b.cmp(a));
+		indices.dedup();
 
-		(0..leaves_count).for_each(|_| known.push(false));
-		indices
-			.iter()
-			.for_each(|i| known[(half_leaves_count + *i) as usize] = true);
+		// Splitting the sorted indices into runs is enough, interleaving pages never share more.
+		//
+		// Each path joins the previous one at their lowest common ancestor, so a run of `m` leaves
+		// covers `depth + 1` nodes plus the heights of these ancestors. Every covered node but the
+		// root has a sibling unless both children are covered, that's `covered - 2 * m + 1`.
+		let depth = self.depth() as usize;
+		let mut heights = Vec::with_capacity(indices.len());
+
+		heights.push(0);
+		indices.windows(2).for_each(|pair| {
+			let height = (u32::BITS - (pair[0] ^ pair[1]).leading_zeros()) as usize;
+
+			heights.push(heights[heights.len() - 1] + height);
+		});
+
+		let siblings_count =
+			|k: usize, j: usize| depth + 2 + heights[j - 1] - heights[k] - 2 * (j - k);
+		// The siblings count, the pages count and the first index of the last page of the best
+		// split of `indices[..j]`.
+		let mut best = vec![(0, 0, 0); indices.len() + 1];
+
+		for j in 1..=indices.len() {
+			best[j] = (0..j)
+				.filter_map(|k| {
+					let count = siblings_count(k, j);
+
+					(j - k == 1 || count <= max_siblings_per_page)
+						.then(|| (best[k].0 + count, best[k].1 + 1, k))
+				})
+				.min()
+				.expect("a single leaf always fits; qed");
+		}
+
+		let mut pages = Vec::with_capacity(best[indices.len()].1);
+		let mut j = indices.len();
+
+		while j > 0 {
+			let k = best[j].2;
+
+			pages.push(self.proof_of(&indices[k..j]));
+
+			j = k;
+		}
+
+		pages.reverse();
 
-		let mut proof = Vec::new();
+		pages
+	}
+
+	/// Same as [`SparseMerkleTree::proof_of`], without building the tree.
+	///
+	/// The leaves are streamed through once and counted on the way, e.g. a `filter` is fine. Only
+	/// the `O(log n)` frontier and the requested paths are kept in memory.
+	pub fn prove_from_leaves<L>(leaves: L, indices: &[u32]) -> Proof<H, M>
+	where
+		L: IntoIterator<Item = H>,
+	{
+		let mut sorted_indices = indices.to_vec();
+
+		sorted_indices.sort_unstable();
+
+		let mut requested = Vec::with_capacity(indices.len());
+		// `(height, position in the level, node, whether the subtree contains a requested leaf)`,
+		// the node indices are unknown until all the leaves are counted.
+		let mut frontier = Vec::<(u32, u32, H, bool)>::new();
+		// `(height, position in the level, sibling)`
+		let mut siblings = Vec::new();
+		let mut push = |i: u32, leaf: H, known: bool| {
+			frontier.push((0, i, leaf, known));
+
+			// Merge while the top two nodes are siblings.
+			while let [.., (l_h, l_p, _, _), (r_h, r_p, _, _)] = frontier.as_slice() {
+				if l_h != r_h || *l_p + 1 != *r_p || r_p & 1 == 0 {
+					break;
+				}
+
+				let (h, r_p, r, r_known) = frontier.pop().expect("checked above; qed");
+				let (_, _, l, l_known) = frontier.pop().expect("checked above; qed");
 
-		(1..half_leaves_count).rev().for_each(|i| {
-			let i = i as usize;
-			let j = i * 2;
-			let k = j + 1;
-			let l = known[j];
-			let r = known[k];
+				if l_known && !r_known {
+					siblings.push((h, r_p, r.clone()));
+				}
+				if !l_known && r_known {
+					siblings.push((h, r_p - 1, l.clone()));
+				}
 
-			if l && !r {
-				proof.push(self.nodes[k].clone());
+				frontier.push((h + 1, r_p / 2, M::merge(&l, &r), l_known || r_known));
 			}
-			if !l && r {
-				proof.push(self.nodes[j].clone());
+		};
+		let mut non_empty_leaves_count = 0;
+
+		for leaf in leaves {
+			if non_empty_leaves_count == 1 << MAX_DEPTH {
+				warn!("prove_from_leaves::Too many leaves.");
+
+				return Proof::default();
 			}
 
-			known[i] = l || r;
-		});
+			let known = sorted_indices
+				.binary_search(&non_empty_leaves_count)
+				.is_ok();
+
+			if known {
+				requested.push((non_empty_leaves_count, leaf.clone()));
+			}
+
+			push(non_empty_leaves_count, leaf, known);
+
+			non_empty_leaves_count += 1;
+		}
+
+		if indices.iter().any(|i| *i >= non_empty_leaves_count) {
+			warn!("prove_from_leaves::Index out of bounds.");
+
+			return Proof::default();
+		}
+
+		let half_leaves_count = non_empty_to_half_leaves_count(non_empty_leaves_count);
+
+		(non_empty_leaves_count..half_leaves_count).for_each(|i| push(i, M::empty(), false));
+
+		// Same order as the verification consumes them, from the bottom right to the top left.
+		siblings.sort_unstable_by(|(a_h, a_p, _), (b_h, b_p, _)| a_h.cmp(b_h).then(b_p.cmp(a_p)));
 
 		Proof {
-			root: self.root(),
+			root: frontier
+				.pop()
+				.map(|(_, _, root, _)| root)
+				.unwrap_or_else(M::empty),
 			leaves_with_index: indices
 				.iter()
 				.map(|i| {
-					let i = half_leaves_count + *i;
+					let leaf = requested
+						.iter()
+						.find(|(j, _)| j == i)
+						.map(|(_, leaf)| leaf.clone())
+						.expect("every requested leaf was visited; qed");
 
-					(i, self.nodes[i as usize].clone())
+					(half_leaves_count + *i, leaf)
 				})
 				.collect(),
-			proof,
+			proof: siblings
+				.into_iter()
+				.map(|(_, _, sibling)| sibling)
+				.collect(),
+			non_empty_leaves_count: None,
+			_merge: PhantomData,
 		}
 	}
 
-	pub fn verify<M>(proof: Proof<H>) -> bool
+	pub fn verify(proof: Proof<H, M>) -> bool {
+		Self::verify_with_instrument(proof, &mut ())
+	}
+
+	pub fn verify_with_instrument<I>(proof: Proof<H, M>, instrument: &mut I) -> bool
 	where
-		M: Merge<Item = H>,
+		I: Instrument,
 	{
-		let Proof {
-			root,
-			leaves_with_index: mut nodes_with_indices,
-			proof,
-		} = proof;
-
-		if nodes_with_indices.is_empty() {
-			return false;
-		}
+		#[cfg(feature = "tracing")]
+		let _span = tracing::debug_span!(
+			target: LOG_TARGET,
+			"verify",
+			leaves_count = proof.leaves_with_index.len(),
+			proof_size = proof.proof.len(),
+			verified = tracing::field::Empty
+		)
+		.entered();
 
-		#[cfg(feature = "debug")]
-		{
-			log::debug!("verify::root: {:?}", root);
-			log::debug!("verify::nodes_with_indices: {:?}", nodes_with_indices);
-			log::debug!("verify::proof: {:?}", proof);
-		}
+		instrument.enter(Operation::Verify);
 
-		// Use ptr to avoid extra vector allocation(`remove`).
-		let mut p_i = 0;
-		let mut n_i = 0;
+		let verified = Self::verify_inner(proof, instrument);
 
-		while n_i < nodes_with_indices.len() {
-			let i = nodes_with_indices[n_i].0;
-			// Cache the current `n_i`.
-			let n_j = n_i;
+		instrument.exit(Operation::Verify);
 
-			n_i += 1;
+		#[cfg(feature = "tracing")]
+		_span.record("verified", verified);
 
-			if i == 1 {
-				return &root == &nodes_with_indices[n_j].1;
-			}
-			// Index starts from `0`, left nodes' index is an even number.
-			else if i % 2 == 0 {
-				if p_i == proof.len() {
-					return false;
-				}
+		verified
+	}
 
-				nodes_with_indices.push((i / 2, M::merge(&nodes_with_indices[n_j].1, &proof[p_i])));
-				p_i += 1;
-			}
-			// Check the next node if exists.
-			// Notice that the `n_i` was already `+1`.
-			else if n_i != nodes_with_indices.len() && nodes_with_indices[n_i].0 == i - 1 {
-				nodes_with_indices.push((
-					i / 2,
-					M::merge(&nodes_with_indices[n_i].1, &nodes_with_indices[n_j].1),
-				));
-				n_i += 1;
-			} else {
-				if p_i == proof.len() {
-					return false;
-				}
+	/// Same as [`SparseMerkleTree::verify`], but reject the proofs deeper than `max_depth`,
+	/// the same as a verifier with a fixed-size loop.
+	///
+	/// Every leaf is checked, and the proof must pass [`Proof::validate_structure`].
+	pub fn verify_with_max_depth(proof: Proof<H, M>, max_depth: u32) -> bool {
+		!proof.leaves_with_index.is_empty()
+			&& proof
+				.leaves_with_index
+				.iter()
+				.all(|(i, _)| node_depth(*i).is_some_and(|depth| depth <= max_depth))
+			&& proof.validate_structure().is_ok()
+			&& Self::verify(proof)
+	}
 
-				nodes_with_indices.push((i / 2, M::merge(&proof[p_i], &nodes_with_indices[n_j].1)));
-				p_i += 1;
-			}
+	/// Same as [`SparseMerkleTree::verify`], but abort after `max_merges` merges.
+	///
+	/// Bound the weight of the attacker-supplied proofs, the proofs which need more merges are
+	/// rejected with [`BudgetExceeded`] no matter they are valid or not.
+	pub fn verify_with_budget(proof: Proof<H, M>, max_merges: u32) -> Result<bool, BudgetExceeded> {
+		let mut budget = MergeBudget::new(max_merges);
+		let verified = Self::verify_with_instrument(proof, &mut budget);
 
-			#[cfg(feature = "debug")]
-			log::debug!("verify::nodes_with_indices: {:?}", nodes_with_indices);
+		if budget.exceeded() {
+			Err(BudgetExceeded)
+		} else {
+			Ok(verified)
 		}
+	}
 
-		false
+	/// Check the proof against several acceptable roots, e.g. the last N finalized ones.
+	///
+	/// The root is computed once, the root carried by the proof is ignored.
+	/// Return the index of the first matched root.
+	pub fn verify_against_roots(roots: &[H], proof: &Proof<H, M>) -> Option<usize> {
+		let computed_root = Self::compute_root(
+			&MergeAdapter::<M>::new(),
+			proof.leaves_with_index.clone(),
+			&proof.proof,
+			&mut (),
+		)?;
+
+		roots.iter().position(|root| root == &computed_root)
 	}
-}
 
-#[cfg_attr(feature = "debug", derive(Debug))]
-#[derive(Default)]
-pub struct Proof<H>
-where
-	H: Default,
+	fn verify_inner<I>(proof: Proof<H, M>, instrument: &mut I) -> bool
+	where
+		I: Instrument,
+	{
+		let Proof {
+			root,
+			leaves_with_index,
+			proof,
+			..
+		} = proof;
+
+		#[cfg(feature = "debug")]
+		debug!("verify::root: {:?}", root);
+
+		Self::compute_root(
+			&MergeAdapter::<M>::new(),
+			leaves_with_index,
+			&proof,
+			instrument,
+		)
+		.as_ref() == Some(&root)
+	}
+}
+
+impl<H, M> SparseMerkleTree<H, M>
+where
+	H: Clone + MaybeDebug + PartialEq,
+{
+	/// The tree without any leaf, see [`SparseMerkleTree::is_empty`].
+	///
+	/// It has no node, its root is [`Merge::empty`]. There is nothing to prove, so
+	/// [`SparseMerkleTree::proof_of`] returns a proof without any leaf, which never verifies.
+	pub fn empty() -> Self {
+		Self {
+			nodes: Vec::new(),
+			non_empty_leaves_count: 0,
+			audit_log: None,
+			_merge: PhantomData,
+		}
+	}
+
+	/// Built from no leaf, e.g. [`SparseMerkleTree::new`] with an empty iterator.
+	///
+	/// The fixed-depth trees of [`SparseMerkleTree::new_with_depth`] keep their padding, they are
+	/// not empty even without any non-empty leaf.
+	pub fn is_empty(&self) -> bool {
+		self.nodes.is_empty()
+	}
+
+	/// All the nodes, `nodes[1]` is the root and `nodes[capacity + i]` is the leaf `i`.
+	///
+	/// `nodes[0]` is unused, there is no node at all in the empty tree.
+	pub fn as_nodes(&self) -> &[H] {
+		&self.nodes
+	}
+
+	/// Take the nodes, e.g. to snapshot the tree, see [`SparseMerkleTree::from_nodes`].
+	pub fn into_nodes(self) -> Vec<H> {
+		self.nodes
+	}
+
+	/// Modify the nodes in place.
+	///
+	/// Nothing is re-merged, the caller must keep every node the merge of its children.
+	/// Otherwise, the root and the proofs are meaningless.
+	pub fn as_nodes_mut_unchecked(&mut self) -> &mut [H] {
+		&mut self.nodes
+	}
+
+	/// The length of [`SparseMerkleTree::as_nodes`], including the unused `nodes[0]`.
+	pub fn nodes_count(&self) -> u32 {
+		self.nodes.len() as _
+	}
+
+	#[deprecated(note = "this is the nodes count, use `nodes_count` or `capacity` instead")]
+	pub fn leaves_count(&self) -> u32 {
+		self.nodes_count()
+	}
+
+	/// The number of the leaf slots, including the padding ones.
+	///
+	/// The leaf at index `i` is `nodes[capacity + i]`.
+	pub fn capacity(&self) -> u32 {
+		self.nodes_count() / 2
+	}
+
+	/// The number of the levels below the root, which is also the length of a single leaf proof.
+	pub fn depth(&self) -> u32 {
+		// The empty tree is treated as a single leaf one.
+		self.capacity().max(1).trailing_zeros()
+	}
+
+	pub fn non_empty_leaves_count(&self) -> u32 {
+		self.non_empty_leaves_count
+	}
+
+	/// The non-empty leaves, in the index order.
+	pub fn leaves(&self) -> &[H] {
+		let capacity = self.capacity() as usize;
+
+		&self.nodes[capacity..capacity + self.non_empty_leaves_count as usize]
+	}
+
+	/// Whether `leaf` is the non-empty leaf at `index`, without building a proof.
+	pub fn contains(&self, index: u32, leaf: &H) -> bool {
+		self.leaves().get(index as usize) == Some(leaf)
+	}
+
+	/// The index of the first non-empty leaf equal to `leaf`.
+	///
+	/// Scan all the leaves, build a [`LeafIndex`](keyed::LeafIndex) for the repeated lookups.
+	pub fn contains_hash(&self, leaf: &H) -> Option<u32> {
+		self.leaves().iter().position(|l| l == leaf).map(|i| i as _)
+	}
+
+	/// Rebuild the internal nodes with another merger, e.g. to migrate the hash function.
+	///
+	/// The leaf layer is reused, the padding leaves are replaced with `M2::empty()`. The capacity
+	/// is kept, so are the fixed-depth trees of [`SparseMerkleTree::new_with_depth`].
+	/// Same as [`SparseMerkleTree::update_leaf`], only for [`Padding::Empty`].
+	pub fn rehash<M2>(&self) -> SparseMerkleTree<H, M2>
+	where
+		M2: Merge<Item = H>,
+	{
+		let mut nodes = self.rehashed_leaves::<M2>();
+
+		for i in (1..self.capacity() as usize).rev() {
+			let (parents, children) = nodes.split_at_mut(i * 2);
+
+			M2::merge_into(&mut parents[i], &children[0], &children[1]);
+		}
+
+		SparseMerkleTree {
+			nodes,
+			non_empty_leaves_count: self.non_empty_leaves_count,
+			audit_log: None,
+			_merge: PhantomData,
+		}
+	}
+
+	/// The nodes of the same capacity, the internal nodes are left to be merged.
+	fn rehashed_leaves<M2>(&self) -> Vec<H>
+	where
+		M2: Merge<Item = H>,
+	{
+		let capacity = self.capacity() as usize;
+		let mut nodes = Vec::with_capacity(self.nodes.len());
+
+		nodes.resize(capacity, M2::empty());
+		nodes.extend_from_slice(self.leaves());
+		nodes.resize(capacity * 2, M2::empty());
+
+		nodes
+	}
+
+	fn root_inner<D>(&self, merger: &D) -> H
+	where
+		D: ?Sized + DynMerge<H>,
+	{
+		if self.nodes_count() == 0 {
+			merger.empty()
+		} else {
+			self.nodes[1].clone()
+		}
+	}
+
+	fn build<D, L, I>(
+		merger: &D,
+		leaves: L,
+		padding: Padding,
+		depth: Option<u32>,
+		default_nodes: Option<&DefaultNodes<H, M>>,
+		instrument: &mut I,
+	) -> Self
+	where
+		D: ?Sized + DynMerge<H>,
+		L: IntoIterator<Item = H>,
+		I: Instrument,
+	{
+		// Don't trust the size hint, the tree is sized from the leaves count.
+		let leaves = leaves.into_iter().collect::<Vec<_>>();
+		#[cfg(feature = "tracing")]
+		let _span = tracing::debug_span!(
+			target: LOG_TARGET,
+			"new",
+			non_empty_leaves_count = leaves.len(),
+			half_leaves_count = tracing::field::Empty
+		)
+		.entered();
+
+		instrument.enter(Operation::New);
+
+		let non_empty_leaves_count = leaves.len() as u32;
+
+		if non_empty_leaves_count == 0 && depth.is_none() {
+			instrument.exit(Operation::New);
+
+			return Self::empty();
+		}
+
+		let half_leaves_count = non_empty_to_half_leaves_count(non_empty_leaves_count)
+			.max(depth.map_or(0, |depth| 1 << depth));
+		let leaves_count = half_leaves_count * 2;
+		let mut nodes = Vec::with_capacity(leaves_count as _);
+
+		#[cfg(feature = "tracing")]
+		_span.record("half_leaves_count", half_leaves_count);
+		#[cfg(feature = "debug")]
+		{
+			debug!("new::non_empty_leaves_count: {}", non_empty_leaves_count);
+			debug!("new::half_leaves_count: {}", half_leaves_count);
+		}
+
+		// Fill the empty leaves.
+		(0..half_leaves_count).for_each(|_| nodes.push(merger.empty()));
+		// Fill the leaves.
+		nodes.extend(leaves);
+
+		let padding = match padding {
+			Padding::DuplicateLast if non_empty_leaves_count != 0 => Padding::DuplicateLast,
+			_ => Padding::Empty,
+		};
+		let padding_leaf = match padding {
+			Padding::Empty => merger.empty(),
+			Padding::DuplicateLast => nodes[nodes.len() - 1].clone(),
+		};
+
+		// Fill the padding leaves.
+		// `x.next_power_of_two()` must grater/equal than/to `x`; qed
+		(0..half_leaves_count - non_empty_leaves_count)
+			.for_each(|_| nodes.push(padding_leaf.clone()));
+		// Build the SMT level by level, from the bottom to the top.
+		let depth = half_leaves_count.trailing_zeros();
+
+		(1..=depth).for_each(|height| {
+			let level = depth - height;
+
+			((1 << level)..(2 << level)).for_each(|i: u32| {
+				// The first leaf of the subtree is a padding leaf.
+				if (i << height) - half_leaves_count >= non_empty_leaves_count {
+					match padding {
+						Padding::Empty => {
+							if let Some(node) = default_nodes.and_then(|d| d.get(height)) {
+								nodes[i as usize] = node.clone();
+
+								return;
+							}
+						}
+						// Not the leftmost node, since there is at least one non-empty leaf.
+						Padding::DuplicateLast => {
+							nodes[i as usize] = nodes[i as usize - 1].clone();
+
+							return;
+						}
+					}
+				}
+
+				let i = i as usize;
+				let (parents, children) = nodes.split_at_mut(i * 2);
+
+				merger.merge_into(&mut parents[i], &children[0], &children[1]);
+				instrument.on_merge(Operation::New);
+			});
+		});
+
+		instrument.exit(Operation::New);
+
+		Self {
+			nodes,
+			non_empty_leaves_count,
+			audit_log: None,
+			_merge: PhantomData,
+		}
+	}
+
+	fn proof_of_inner<D>(
+		&self,
+		indices: &[u32],
+		merger: &D,
+		scratch: &mut ProofScratch,
+	) -> Proof<H, M>
+	where
+		D: ?Sized + DynMerge<H>,
+	{
+		let half_leaves_count = self.capacity();
+
+		if indices.iter().any(|i| *i >= self.non_empty_leaves_count()) {
+			warn!("proof_of::Index out of bounds.");
+
+			return Proof {
+				root: merger.empty(),
+				leaves_with_index: Vec::new(),
+				proof: Vec::new(),
+				non_empty_leaves_count: None,
+				_merge: PhantomData,
+			};
+		}
+
+		let proof = siblings_of(&self.nodes, indices, scratch);
+
+		Proof {
+			root: self.root_inner(merger),
+			leaves_with_index: indices
+				.iter()
+				.map(|i| {
+					let i = half_leaves_count + *i;
+
+					(i, self.nodes[i as usize].clone())
+				})
+				.collect(),
+			proof,
+			non_empty_leaves_count: None,
+			_merge: PhantomData,
+		}
+	}
+
+	/// Recompute the root from the leaves and the siblings, `None` if the proof is incomplete.
+	fn compute_root<D, I>(
+		merger: &D,
+		nodes_with_indices: Vec<(u32, H)>,
+		proof: &[H],
+		instrument: &mut I,
+	) -> Option<H>
+	where
+		D: ?Sized + DynMerge<H>,
+		I: Instrument,
+	{
+		if nodes_with_indices.is_empty() {
+			return None;
+		}
+
+		#[cfg(feature = "debug")]
+		{
+			trace!("verify::nodes_with_indices: {:?}", nodes_with_indices);
+			debug!("verify::proof: {:?}", proof);
+		}
+
+		// Reuse the consumed nodes as the merge outputs.
+		let mut nodes_with_indices = VecDeque::from(nodes_with_indices);
+		let mut spare = None;
+		let mut p_i = 0;
+
+		while let Some((i, node)) = nodes_with_indices.pop_front() {
+			if i == 1 {
+				return Some(node);
+			}
+			if !instrument.should_merge(Operation::Verify) {
+				return None;
+			}
+
+			let parent =
+				// Index starts from `0`, left nodes' index is an even number.
+				if i & 1 == 0 {
+					let r = proof.get(p_i)?;
+
+					p_i += 1;
+
+					merge_into_spare(merger, &mut spare, &node, r)
+				}
+				// Check the next node if exists.
+				else if matches!(nodes_with_indices.front(), Some((j, _)) if *j == i - 1) {
+					let (_, l) = nodes_with_indices.pop_front()?;
+
+					merge_into_spare(merger, &mut spare, &l, &node)
+				} else {
+					let l = proof.get(p_i)?;
+
+					p_i += 1;
+
+					merge_into_spare(merger, &mut spare, l, &node)
+				};
+
+			instrument.on_merge(Operation::Verify);
+			spare = Some(node);
+			nodes_with_indices.push_back((i / 2, parent));
+
+			#[cfg(feature = "debug")]
+			trace!("verify::nodes_with_indices: {:?}", nodes_with_indices);
+		}
+
+		None
+	}
+}
+
+impl<H, M> SparseMerkleTree<H, M>
+where
+	H: Clone + MaybeDebug + PartialEq,
+	M: MergeCount<Item = H>,
 {
+	/// `merge(root, count(non_empty_leaves_count))`
+	pub fn committed_root(&self) -> H {
+		M::merge(&self.root(), &M::count(self.non_empty_leaves_count))
+	}
+
+	/// Same as [`SparseMerkleTree::proof_of`], but against the [`SparseMerkleTree::committed_root`].
+	///
+	/// The proof carries the leaves count.
+	pub fn committed_proof_of<I>(&self, indices: I) -> Proof<H, M>
+	where
+		I: IntoIterator,
+		I::Item: Borrow<u32>,
+	{
+		let indices = collect_indices(indices);
+		let mut proof = self.proof_of_traced(&indices, &mut (), &mut ProofScratch::new());
+
+		if !proof.leaves_with_index.is_empty() {
+			proof.root = self.committed_root();
+			proof.non_empty_leaves_count = Some(self.non_empty_leaves_count);
+		}
+
+		self.audit_proof(&indices, &proof);
+
+		proof
+	}
+
+	/// Verify a proof from [`SparseMerkleTree::committed_proof_of`].
+	///
+	/// Besides the root, the leaves must be in the range implied by the leaves count.
+	pub fn verify_committed(proof: Proof<H, M>) -> bool {
+		let Proof {
+			root,
+			leaves_with_index,
+			proof,
+			non_empty_leaves_count,
+			..
+		} = proof;
+		let non_empty_leaves_count = match non_empty_leaves_count {
+			Some(non_empty_leaves_count) => non_empty_leaves_count,
+			None => return false,
+		};
+		let half_leaves_count = match checked_half_leaves_count(non_empty_leaves_count) {
+			Some(half_leaves_count) => half_leaves_count,
+			None => return false,
+		};
+
+		if leaves_with_index.iter().any(|(i, _)| {
+			*i < half_leaves_count || *i - half_leaves_count >= non_empty_leaves_count
+		}) {
+			return false;
+		}
+
+		Self::compute_root(
+			&MergeAdapter::<M>::new(),
+			leaves_with_index,
+			&proof,
+			&mut (),
+		)
+		.map(|computed_root| M::merge(&computed_root, &M::count(non_empty_leaves_count)))
+		.as_ref() == Some(&root)
+	}
+}
+
+pub struct Proof<H, M> {
 	root: H,
 	leaves_with_index: Vec<(u32, H)>,
 	proof: Vec<H>,
+	/// Only present in the proofs of the committed root.
+	non_empty_leaves_count: Option<u32>,
+	_merge: PhantomData<M>,
+}
+// Implement these manually, the derived ones would require `M` to implement them too.
+impl<H, M> Debug for Proof<H, M>
+where
+	H: Debug,
+{
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		f.debug_struct("Proof")
+			.field("root", &self.root)
+			.field("leaves_with_index", &self.leaves_with_index)
+			.field("proof", &self.proof)
+			.field("non_empty_leaves_count", &self.non_empty_leaves_count)
+			.finish()
+	}
+}
+impl<H, M> Clone for Proof<H, M>
+where
+	H: Clone,
+{
+	fn clone(&self) -> Self {
+		Self {
+			root: self.root.clone(),
+			leaves_with_index: self.leaves_with_index.clone(),
+			proof: self.proof.clone(),
+			non_empty_leaves_count: self.non_empty_leaves_count,
+			_merge: PhantomData,
+		}
+	}
+}
+impl<H, M> Default for Proof<H, M>
+where
+	M: Merge<Item = H>,
+{
+	fn default() -> Self {
+		Self {
+			root: M::empty(),
+			leaves_with_index: Vec::new(),
+			proof: Vec::new(),
+			non_empty_leaves_count: None,
+			_merge: PhantomData,
+		}
+	}
+}
+impl<H, M> PartialEq for Proof<H, M>
+where
+	H: PartialEq,
+{
+	fn eq(&self, other: &Self) -> bool {
+		self.root == other.root
+			&& self.leaves_with_index == other.leaves_with_index
+			&& self.proof == other.proof
+			&& self.non_empty_leaves_count == other.non_empty_leaves_count
+	}
+}
+impl<H, M> Eq for Proof<H, M> where H: Eq {}
+impl<H, M> PartialOrd for Proof<H, M>
+where
+	H: PartialOrd,
+{
+	/// Lexicographic over the root, the leaves, the siblings and the leaves count.
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		match self.root.partial_cmp(&other.root)? {
+			Ordering::Equal => {}
+			ordering => return Some(ordering),
+		}
+		match self
+			.leaves_with_index
+			.partial_cmp(&other.leaves_with_index)?
+		{
+			Ordering::Equal => {}
+			ordering => return Some(ordering),
+		}
+		match self.proof.partial_cmp(&other.proof)? {
+			Ordering::Equal => {}
+			ordering => return Some(ordering),
+		}
+
+		self.non_empty_leaves_count
+			.partial_cmp(&other.non_empty_leaves_count)
+	}
+}
+impl<H, M> Ord for Proof<H, M>
+where
+	H: Ord,
+{
+	fn cmp(&self, other: &Self) -> Ordering {
+		self.root
+			.cmp(&other.root)
+			.then_with(|| self.leaves_with_index.cmp(&other.leaves_with_index))
+			.then_with(|| self.proof.cmp(&other.proof))
+			.then_with(|| {
+				self.non_empty_leaves_count
+					.cmp(&other.non_empty_leaves_count)
+			})
+	}
+}
+impl<H, M> Hash for Proof<H, M>
+where
+	H: Hash,
+{
+	fn hash<S>(&self, state: &mut S)
+	where
+		S: Hasher,
+	{
+		self.root.hash(state);
+		self.leaves_with_index.hash(state);
+		self.proof.hash(state);
+		self.non_empty_leaves_count.hash(state);
+	}
 }
-impl<H> Proof<H>
+impl<H, M> Proof<H, M>
 where
-	H: Clone + Default,
+	H: Clone,
 {
+	pub fn root(&self) -> &H {
+		&self.root
+	}
+
 	/// Avoid to use this function as far as possible.
 	///
 	/// Pass the `indices` in descend order to [`SparseMerkleRoot::proof_of`],
@@ -255,8 +1460,423 @@ where
 
 		self
 	}
+
+	/// The depth of the proven leaves, `None` if there isn't any leaf.
+	pub fn depth(&self) -> Option<u32> {
+		self.leaves_with_index
+			.first()
+			.and_then(|(i, _)| node_depth(*i))
+	}
+
+	/// Check the shape of the proof without any hashing.
+	///
+	/// A proof which passes this might still fail the verification,
+	/// but one which doesn't pass this will never be verified.
+	pub fn validate_structure(&self) -> Result<(), ProofError> {
+		let depth = match self.leaves_with_index.first() {
+			Some((i, _)) => node_depth(*i).ok_or(ProofError::InvalidIndex)?,
+			None => return Err(ProofError::NoLeaves),
+		};
+
+		if let Some(non_empty_leaves_count) = self.non_empty_leaves_count {
+			let half_leaves_count = checked_half_leaves_count(non_empty_leaves_count)
+				.ok_or(ProofError::InvalidIndex)?;
+
+			if half_leaves_count.trailing_zeros() != depth {
+				return Err(ProofError::InvalidIndex);
+			}
+		}
+
+		let mut level = Vec::<u32>::with_capacity(self.leaves_with_index.len());
+
+		for (i, _) in &self.leaves_with_index {
+			if node_depth(*i) != Some(depth) {
+				return Err(ProofError::InvalidIndex);
+			}
+			if let Some(last) = level.last() {
+				if i == last {
+					return Err(ProofError::DuplicateLeaf);
+				}
+				if i > last {
+					return Err(ProofError::NotDescending);
+				}
+			}
+
+			level.push(*i);
+		}
+
+		// Walk up the tree the same way as the verification does, count the required siblings.
+		let mut expected = 0;
+
+		while level[0] > 1 {
+			let mut parents = Vec::with_capacity(level.len());
+			let mut k = 0;
+
+			while k < level.len() {
+				let i = level[k];
+
+				if i & 1 == 1 && level.get(k + 1) == Some(&(i - 1)) {
+					k += 2;
+				} else {
+					expected += 1;
+					k += 1;
+				}
+
+				parents.push(i / 2);
+			}
+
+			level = parents;
+		}
+
+		if expected == self.proof.len() {
+			Ok(())
+		} else {
+			Err(ProofError::SiblingsCountMismatch {
+				expected,
+				actual: self.proof.len(),
+			})
+		}
+	}
+
+	/// Bring the proof into the canonical form, so that the equal proofs are also byte-equal.
+	///
+	/// Sort the leaves in the descending order, remove the duplicate `(index, leaf)` pairs and
+	/// the siblings which are no longer required by the deduplicated leaves.
+	///
+	/// Return `false` and leave the proof untouched if its siblings don't match its leaves.
+	pub fn normalize(&mut self) -> bool
+	where
+		H: PartialEq,
+	{
+		let mut normalized = self.clone();
+
+		normalized.sort();
+
+		// The verification consumes one sibling per duplicate leaf, map them by the node index.
+		let known = normalized.sibling_indices().map(|sibling_indices| {
+			let mut known = sibling_indices
+				.into_iter()
+				.zip(normalized.proof.iter().cloned())
+				.collect::<Vec<_>>();
+
+			known.sort_by_key(|(i, _)| *i);
+
+			known
+		});
+
+		normalized.leaves_with_index.dedup();
+
+		if let Some(known) = known {
+			let Some(proof) = normalized
+				.sibling_indices_unchecked()
+				.into_iter()
+				.map(|i| {
+					known
+						.binary_search_by_key(&i, |(j, _)| *j)
+						.ok()
+						.map(|k| known[k].1.clone())
+				})
+				.collect()
+			else {
+				return false;
+			};
+
+			normalized.proof = proof;
+		}
+		// Otherwise the siblings might be issued for the deduplicated leaves already, e.g. by
+		// `proof_of`.
+		else if normalized.sibling_indices().is_none() {
+			return false;
+		}
+
+		*self = normalized;
+
+		true
+	}
+
+	/// The node index of each sibling, in the same order as the verification consumes them.
+	///
+	/// `None` if the siblings count doesn't match the leaves.
+	fn sibling_indices(&self) -> Option<Vec<u32>> {
+		let sibling_indices = self.sibling_indices_unchecked();
+
+		(sibling_indices.len() == self.proof.len()).then_some(sibling_indices)
+	}
+
+	fn sibling_indices_unchecked(&self) -> Vec<u32> {
+		let mut nodes = self
+			.leaves_with_index
+			.iter()
+			.map(|(i, _)| *i)
+			.collect::<VecDeque<_>>();
+		let mut sibling_indices = Vec::with_capacity(self.proof.len());
+
+		while let Some(i) = nodes.pop_front() {
+			if i <= 1 {
+				break;
+			}
+
+			if i & 1 == 0 {
+				sibling_indices.push(i + 1);
+			} else if nodes.front() == Some(&(i - 1)) {
+				nodes.pop_front();
+			} else {
+				sibling_indices.push(i - 1);
+			}
+
+			nodes.push_back(i / 2);
+		}
+
+		sibling_indices
+	}
+}
+
+impl<H, M> Proof<H, M>
+where
+	H: Clone + MaybeDebug + PartialEq,
+	M: Merge<Item = H>,
+{
+	/// Patch the proof with the leaf updates `(index, leaf)` since it was issued.
+	///
+	/// Only the proven leaves and the siblings which are leaves themselves can be patched,
+	/// `None` if any other leaf is updated. A leaf update inside a sibling subtree requires the
+	/// whole subtree to rehash.
+	///
+	/// The leaves must be in the descending order, the same as the verification requires.
+	/// The proofs of the committed root are not supported, since the leaves count isn't updated.
+	pub fn refresh(&self, updates: &[(u32, H)]) -> Option<Self> {
+		if self.non_empty_leaves_count.is_some() {
+			return None;
+		}
+
+		let depth = node_depth(self.leaves_with_index.first()?.0)?;
+		let sibling_indices = self.sibling_indices()?;
+		let mut proof = self.clone();
+
+		for (index, leaf) in updates {
+			let i = (1_u32 << depth)
+				.checked_add(*index)
+				.filter(|i| node_depth(*i) == Some(depth))?;
+
+			if let Some((_, l)) = proof.leaves_with_index.iter_mut().find(|(j, _)| *j == i) {
+				*l = leaf.clone();
+			} else {
+				let k = sibling_indices.iter().position(|j| *j == i)?;
+
+				proof.proof[k] = leaf.clone();
+			}
+		}
+
+		proof.root = SparseMerkleTree::<H, M>::compute_root(
+			&MergeAdapter::<M>::new(),
+			proof.leaves_with_index.clone(),
+			&proof.proof,
+			&mut (),
+		)?;
+
+		Some(proof)
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TreeError {
+	/// The nodes count doesn't match the non-empty leaves count.
+	InvalidNodesCount,
+	/// The node at this index isn't the merge of its children.
+	InconsistentNode(u32),
+	/// The leaves don't fit in the tree of the given depth.
+	TooManyLeaves,
+	/// The depth is larger than [`MAX_DEPTH`].
+	InvalidDepth,
+	/// There is no non-empty leaf at this index.
+	IndexOutOfBounds(u32),
+}
+
+/// The verification needs more merges than the budget.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BudgetExceeded;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProofError {
+	NoLeaves,
+	/// The index is `0` or the leaves are not on the same level.
+	InvalidIndex,
+	/// The leaves are not in the descending order.
+	NotDescending,
+	DuplicateLeaf,
+	SiblingsCountMismatch {
+		expected: usize,
+		actual: usize,
+	},
+}
+
+/// Accept the indices from any iterator, e.g. an array, a slice or a `BTreeSet`.
+fn collect_indices<I>(indices: I) -> Vec<u32>
+where
+	I: IntoIterator,
+	I::Item: Borrow<u32>,
+{
+	indices.into_iter().map(|i| *i.borrow()).collect()
+}
+
+/// The root is at depth `0`.
+fn node_depth(i: u32) -> Option<u32> {
+	if i == 0 {
+		None
+	} else {
+		Some(u32::BITS - 1 - i.leading_zeros())
+	}
+}
+
+/// Collect the siblings to prove `indices` of the lowest level of `nodes`, bottom-up.
+fn siblings_of<H>(nodes: &[H], indices: &[u32], scratch: &mut ProofScratch) -> Vec<H>
+where
+	H: Clone,
+{
+	let leaves_count = nodes.len();
+	let half_leaves_count = leaves_count / 2;
+	let known = &mut scratch.0;
+
+	// One bit per node.
+	known.clear();
+	known.resize(leaves_count.div_ceil(64), 0);
+
+	let set = |known: &mut Vec<u64>, i: usize| known[i / 64] |= 1 << (i % 64);
+	let get = |known: &Vec<u64>, i: usize| (known[i / 64] >> (i % 64)) & 1 == 1;
+
+	indices
+		.iter()
+		.for_each(|i| set(known, half_leaves_count + *i as usize));
+
+	let mut proof = Vec::new();
+
+	(1..half_leaves_count).rev().for_each(|i| {
+		let j = i * 2;
+		let k = j + 1;
+		let l = get(known, j);
+		let r = get(known, k);
+
+		if l && !r {
+			proof.push(nodes[k].clone());
+		}
+		if !l && r {
+			proof.push(nodes[j].clone());
+		}
+		if l || r {
+			set(known, i);
+		}
+	});
+
+	proof
+}
+
+/// The reusable buffer of [`SparseMerkleTree::proof_of_with_scratch`].
+///
+/// It's a bitset of the known nodes, one bit per node.
+#[derive(Clone, Debug, Default)]
+pub struct ProofScratch(Vec<u64>);
+impl ProofScratch {
+	pub fn new() -> Self {
+		Self::default()
+	}
+}
+
+/// Nodes of the empty subtrees, indexed by the height of the subtree.
+///
+/// The height `0` is the empty leaf, the height `h` is the merge of two height `h - 1` nodes.
+pub struct DefaultNodes<H, M>(Vec<H>, PhantomData<M>);
+impl<H, M> Clone for DefaultNodes<H, M>
+where
+	H: Clone,
+{
+	fn clone(&self) -> Self {
+		Self(self.0.clone(), PhantomData)
+	}
+}
+impl<H, M> DefaultNodes<H, M>
+where
+	H: Clone,
+	M: Merge<Item = H>,
+{
+	pub fn new(max_height: u32) -> Self {
+		let mut nodes = Vec::with_capacity(max_height as usize + 1);
+
+		nodes.push(M::empty());
+		(0..max_height as usize).for_each(|h| nodes.push(M::merge(&nodes[h], &nodes[h])));
+
+		Self(nodes, PhantomData)
+	}
+}
+impl<H, M> DefaultNodes<H, M> {
+	pub fn get(&self, height: u32) -> Option<&H> {
+		self.0.get(height as usize)
+	}
+
+	pub fn max_height(&self) -> u32 {
+		self.0.len() as u32 - 1
+	}
+}
+
+fn merge_into_spare<H, D>(merger: &D, spare: &mut Option<H>, l: &H, r: &H) -> H
+where
+	D: ?Sized + DynMerge<H>,
+{
+	match spare.take() {
+		Some(mut out) => {
+			merger.merge_into(&mut out, l, r);
+
+			out
+		}
+		None => merger.merge(l, r),
+	}
+}
+
+/// The root of `leaves`, same as [`SparseMerkleTree::new`] then [`SparseMerkleTree::root`].
+///
+/// Call it as `smt_root::<M>(leaves)`, the same goes for [`smt_proof`] and [`smt_verify`].
+pub fn smt_root<M>(leaves: impl IntoIterator<Item = M::Item>) -> M::Item
+where
+	M: Merge,
+	M::Item: Clone + MaybeDebug + PartialEq,
+{
+	SparseMerkleTree::<_, M>::new(leaves).root()
+}
+
+/// The proof of `indices` in `leaves`, the indices could be in any order.
+///
+/// The leaves of the proof are sorted, it's ready for [`smt_verify`].
+pub fn smt_proof<M>(
+	leaves: impl IntoIterator<Item = M::Item>,
+	indices: impl IntoIterator<Item = u32>,
+) -> Proof<M::Item, M>
+where
+	M: Merge,
+	M::Item: Clone + MaybeDebug + PartialEq,
+{
+	let mut proof = SparseMerkleTree::<_, M>::new(leaves).proof_of(indices);
+
+	proof.sort();
+
+	proof
+}
+
+/// Same as [`SparseMerkleTree::verify`].
+pub fn smt_verify<M>(proof: Proof<M::Item, M>) -> bool
+where
+	M: Merge,
+	M::Item: Clone + MaybeDebug + PartialEq,
+{
+	SparseMerkleTree::verify(proof)
 }
 
 pub fn non_empty_to_half_leaves_count(non_empty_leaves_count: u32) -> u32 {
 	non_empty_leaves_count.next_power_of_two()
 }
+
+/// Same as [`non_empty_to_half_leaves_count`], `None` if the tree would be deeper than
+/// [`MAX_DEPTH`].
+fn checked_half_leaves_count(non_empty_leaves_count: u32) -> Option<u32> {
+	non_empty_leaves_count
+		.checked_next_power_of_two()
+		.filter(|half_leaves_count| *half_leaves_count <= 1 << MAX_DEPTH)
+}
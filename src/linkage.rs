@@ -0,0 +1,111 @@
+//! Link two trees over the same leaves but with different mergers, e.g. during a migration.
+//!
+//! A [`Linkage`] proves the same sampled leaves in both trees. The samples are derived from the
+//! two roots and the leaves count, which the auditor knows, so the prover can't pick them.
+//!
+//! Sampling is only probabilistic, a tree which differs in `d` of `n` leaves passes `s` samples
+//! with the probability about `(1 - d / n)^s`. Sample all the leaves, i.e. `s >= n`, to check the
+//! whole leaf layer.
+
+// --- alloc ---
+use alloc::{collections::BTreeSet, vec::Vec};
+// --- sparse-merkle-tree ---
+use crate::*;
+
+/// The same leaves proven against both roots.
+pub struct Linkage<H, M1, M2> {
+	pub a: Proof<H, M1>,
+	pub b: Proof<H, M2>,
+}
+impl<H, M1, M2> Linkage<H, M1, M2>
+where
+	H: AsRef<[u8]> + Clone + MaybeDebug + PartialEq,
+	M1: Merge<Item = H>,
+	M2: Merge<Item = H>,
+{
+	/// `None` if the trees have different leaves, or no leaf at all.
+	pub fn new(
+		a: &SparseMerkleTree<H, M1>,
+		b: &SparseMerkleTree<H, M2>,
+		samples: u32,
+	) -> Option<Self> {
+		if a.leaves().is_empty() || a.leaves() != b.leaves() {
+			return None;
+		}
+
+		let leaves_count = a.non_empty_leaves_count();
+		let indices = challenge::<H, M1>(&a.root(), &b.root(), leaves_count, samples);
+
+		Some(Self {
+			a: a.proof_of(&indices),
+			b: b.proof_of(&indices),
+		})
+	}
+
+	/// Both proofs are valid, and they prove the same leaves at the challenged indices.
+	///
+	/// `leaves_count` and `samples` are set by the auditor, a linkage of another leaves count or
+	/// with fewer samples is rejected. The roots are taken from the proofs, compare them with the
+	/// expected ones through [`Proof::root`].
+	pub fn verify(&self, leaves_count: u32, samples: u32) -> bool {
+		let Some(depth) = self.a.depth() else {
+			return false;
+		};
+
+		if leaves_count == 0
+			|| checked_half_leaves_count(leaves_count)
+				.is_none_or(|half_leaves_count| half_leaves_count.trailing_zeros() != depth)
+		{
+			return false;
+		}
+
+		let indices = challenge::<H, M1>(&self.a.root, &self.b.root, leaves_count, samples);
+
+		self.a.leaves_with_index == self.b.leaves_with_index
+			&& self
+				.a
+				.leaves_with_index
+				.iter()
+				.map(|(i, _)| i.checked_sub(1 << depth))
+				.eq(indices.into_iter().map(Some))
+			&& SparseMerkleTree::verify(self.a.clone())
+			&& SparseMerkleTree::verify(self.b.clone())
+	}
+}
+
+/// The leaf indices in the descending order, all of them if `samples >= leaves_count`.
+///
+/// The seed is `merge(root_a, root_b)`, and each round merges `root_b` into it again. The first 4
+/// bytes of the seed, in big-endian, modulo `leaves_count` is the sample. Stop after
+/// `4 * samples` rounds, so a degenerate merger might yield fewer samples.
+fn challenge<H, M>(root_a: &H, root_b: &H, leaves_count: u32, samples: u32) -> Vec<u32>
+where
+	H: AsRef<[u8]>,
+	M: Merge<Item = H>,
+{
+	if leaves_count == 0 {
+		return Vec::new();
+	}
+	if samples >= leaves_count {
+		return (0..leaves_count).rev().collect();
+	}
+
+	let mut seed = M::merge(root_a, root_b);
+	let mut indices = BTreeSet::new();
+
+	for _ in 0..samples.saturating_mul(4) {
+		if indices.len() == samples as usize {
+			break;
+		}
+
+		let mut bytes = [0; 4];
+		let n = seed.as_ref().len().min(4);
+
+		bytes[..n].copy_from_slice(&seed.as_ref()[..n]);
+		indices.insert(u32::from_be_bytes(bytes) % leaves_count);
+
+		seed = M::merge(&seed, root_b);
+	}
+
+	indices.into_iter().rev().collect()
+}
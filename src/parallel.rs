@@ -0,0 +1,162 @@
+//! Spread the independent operations across the threads, with [`rayon`] or the scoped
+//! [`std::thread`]s.
+
+// --- alloc ---
+use alloc::vec::Vec;
+#[cfg(feature = "threads")]
+use core::num::NonZeroUsize;
+// --- crates.io ---
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+// --- sparse-merkle-tree ---
+use crate::*;
+
+/// Don't split a level into the chunks smaller than this, the threads aren't free.
+const MIN_CHUNK_LEN: usize = 256;
+
+/// The parents of a level chunk, and their children.
+type Chunks<'a, H> = Vec<(&'a mut [H], &'a [H])>;
+
+impl<H, M> SparseMerkleTree<H, M>
+where
+	H: Clone + MaybeDebug + PartialEq + Send + Sync,
+	M: Merge<Item = H>,
+{
+	/// Same as [`SparseMerkleTree::new`], each level is split into the chunks merged by [`rayon`].
+	#[cfg(feature = "parallel")]
+	pub fn new_par<L>(leaves: L) -> Self
+	where
+		L: IntoIterator<Item = H>,
+	{
+		Self::build_chunked(leaves, rayon::current_num_threads(), |chunks| {
+			chunks
+				.into_par_iter()
+				.for_each(|(parents, children)| merge_chunk::<H, M>(parents, children))
+		})
+	}
+
+	/// Same as [`SparseMerkleTree::new_par`], but with `workers` scoped [`std::thread`]s instead
+	/// of [`rayon`].
+	#[cfg(feature = "threads")]
+	pub fn new_with_threads<L>(leaves: L, workers: NonZeroUsize) -> Self
+	where
+		L: IntoIterator<Item = H>,
+	{
+		Self::build_chunked(leaves, workers.get(), |mut chunks| {
+			if chunks.len() == 1 {
+				let (parents, children) = chunks.pop().expect("`chunks` isn't empty; qed");
+
+				return merge_chunk::<H, M>(parents, children);
+			}
+
+			std::thread::scope(|scope| {
+				chunks.into_iter().for_each(|(parents, children)| {
+					scope.spawn(|| merge_chunk::<H, M>(parents, children));
+				});
+			});
+		})
+	}
+
+	/// Build the tree with [`merge_levels`].
+	fn build_chunked<L, F>(leaves: L, workers: usize, run: F) -> Self
+	where
+		L: IntoIterator<Item = H>,
+		F: FnMut(Chunks<H>),
+	{
+		let leaves = leaves.into_iter().collect::<Vec<_>>();
+		let non_empty_leaves_count = leaves.len() as u32;
+
+		if non_empty_leaves_count == 0 {
+			return Self::empty();
+		}
+
+		let half_leaves_count = non_empty_to_half_leaves_count(non_empty_leaves_count) as usize;
+		let mut nodes = Vec::with_capacity(half_leaves_count * 2);
+
+		// Fill the empty leaves, the leaves and the padding leaves.
+		nodes.resize(half_leaves_count, M::empty());
+		nodes.extend(leaves);
+		nodes.resize(half_leaves_count * 2, M::empty());
+
+		merge_levels(&mut nodes, workers, run);
+
+		Self {
+			nodes,
+			non_empty_leaves_count,
+			audit_log: None,
+			_merge: PhantomData,
+		}
+	}
+}
+
+#[cfg(feature = "parallel")]
+impl<H, M> SparseMerkleTree<H, M>
+where
+	H: Clone + MaybeDebug + PartialEq + Send,
+	M: Merge<Item = H> + Send,
+{
+	/// Same as [`SparseMerkleTree::verify`] on each proof, the results are in the same order.
+	pub fn verify_all(proofs: Vec<Proof<H, M>>) -> Vec<bool> {
+		proofs.into_par_iter().map(Self::verify).collect()
+	}
+}
+
+#[cfg(feature = "parallel")]
+impl<H, M> SparseMerkleTree<H, M>
+where
+	H: Clone + MaybeDebug + PartialEq + Send + Sync,
+{
+	/// Same as [`SparseMerkleTree::rehash`], each level is split into the chunks merged by
+	/// [`rayon`].
+	pub fn rehash_par<M2>(&self) -> SparseMerkleTree<H, M2>
+	where
+		M2: Merge<Item = H>,
+	{
+		let mut nodes = self.rehashed_leaves::<M2>();
+
+		merge_levels(&mut nodes, rayon::current_num_threads(), |chunks| {
+			chunks
+				.into_par_iter()
+				.for_each(|(parents, children)| merge_chunk::<H, M2>(parents, children))
+		});
+
+		SparseMerkleTree {
+			nodes,
+			non_empty_leaves_count: self.non_empty_leaves_count,
+			audit_log: None,
+			_merge: PhantomData,
+		}
+	}
+}
+
+/// Merge all the internal nodes level by level from the bottom, `run` merges the chunks of each
+/// level.
+///
+/// A level is split into `workers` chunks at most.
+fn merge_levels<H, F>(nodes: &mut [H], workers: usize, mut run: F)
+where
+	F: FnMut(Chunks<H>),
+{
+	let depth = (nodes.len() / 2).max(1).trailing_zeros();
+
+	(0..depth).rev().for_each(|level| {
+		let (parents, children) = nodes.split_at_mut(2 << level);
+		let parents = &mut parents[1 << level..];
+		let chunk_len = parents.len().div_ceil(workers.max(1)).max(MIN_CHUNK_LEN);
+
+		run(parents
+			.chunks_mut(chunk_len)
+			.zip(children[..2 << level].chunks(chunk_len * 2))
+			.collect());
+	});
+}
+
+fn merge_chunk<H, M>(parents: &mut [H], children: &[H])
+where
+	M: Merge<Item = H>,
+{
+	parents
+		.iter_mut()
+		.zip(children.chunks_exact(2))
+		.for_each(|(parent, pair)| M::merge_into(parent, &pair[0], &pair[1]));
+}
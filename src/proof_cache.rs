@@ -0,0 +1,134 @@
+//! Serve the hot proofs from the memory.
+
+// --- alloc ---
+use alloc::{collections::BTreeMap, vec::Vec};
+// --- sparse-merkle-tree ---
+use crate::*;
+
+/// A [`SparseMerkleTree`] with an LRU cache of its proofs, e.g. for the popular claimants of an
+/// airdrop.
+///
+/// The cache is keyed by the sorted index set, so `[0, 3]` and `[3, 0, 3]` share the same proof.
+/// Every proof commits to the root, any leaf update clears the whole cache.
+pub struct ProofCache<H, M> {
+	tree: SparseMerkleTree<H, M>,
+	capacity: usize,
+	/// The proof and its last use of each index set.
+	proofs: BTreeMap<Vec<u32>, (u64, Proof<H, M>)>,
+	/// The index set of each last use, the least recently used one comes first.
+	uses: BTreeMap<u64, Vec<u32>>,
+	clock: u64,
+}
+impl<H, M> ProofCache<H, M>
+where
+	H: Clone + MaybeDebug + PartialEq,
+	M: Merge<Item = H>,
+{
+	/// Cache `capacity` proofs at most.
+	pub fn new(tree: SparseMerkleTree<H, M>, capacity: usize) -> Self {
+		Self {
+			tree,
+			capacity,
+			proofs: BTreeMap::new(),
+			uses: BTreeMap::new(),
+			clock: 0,
+		}
+	}
+
+	pub fn tree(&self) -> &SparseMerkleTree<H, M> {
+		&self.tree
+	}
+
+	pub fn into_tree(self) -> SparseMerkleTree<H, M> {
+		self.tree
+	}
+
+	/// The number of the cached proofs.
+	pub fn len(&self) -> usize {
+		self.proofs.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.proofs.is_empty()
+	}
+
+	/// Whether the proof of `indices` is cached.
+	pub fn contains<I>(&self, indices: I) -> bool
+	where
+		I: IntoIterator,
+		I::Item: Borrow<u32>,
+	{
+		self.proofs.contains_key(&key_of(&collect_indices(indices)))
+	}
+
+	/// Same as [`SparseMerkleTree::proof_of`], but the leaves are always in the descending order.
+	///
+	/// The proofs of the out of bounds indices are not cached.
+	pub fn proof_of<I>(&mut self, indices: I) -> Proof<H, M>
+	where
+		I: IntoIterator,
+		I::Item: Borrow<u32>,
+	{
+		let key = key_of(&collect_indices(indices));
+
+		self.clock += 1;
+
+		if let Some((last_use, proof)) = self.proofs.get_mut(&key) {
+			let key = self
+				.uses
+				.remove(last_use)
+				.expect("`uses` mirrors `proofs`; qed");
+
+			*last_use = self.clock;
+			self.uses.insert(self.clock, key.clone());
+			self.tree.audit_proof(&key, proof);
+
+			return proof.clone();
+		}
+
+		let proof = self.tree.proof_of(&key);
+
+		if self.capacity == 0 || proof.leaves_with_index.is_empty() {
+			return proof;
+		}
+		if self.proofs.len() == self.capacity {
+			if let Some((_, lru)) = self.uses.pop_first() {
+				self.proofs.remove(&lru);
+			}
+		}
+
+		self.uses.insert(self.clock, key.clone());
+		self.proofs.insert(key, (self.clock, proof.clone()));
+
+		proof
+	}
+
+	/// Same as [`SparseMerkleTree::update_leaf`], and clear the cache.
+	pub fn update_leaf(&mut self, index: u32, leaf: H) -> Result<(), TreeError> {
+		self.tree.update_leaf(index, leaf)?;
+		self.clear();
+
+		Ok(())
+	}
+
+	/// Same as [`SparseMerkleTree::push_leaf`], and clear the cache.
+	pub fn push_leaf(&mut self, leaf: H) -> u32 {
+		self.clear();
+
+		self.tree.push_leaf(leaf)
+	}
+
+	pub fn clear(&mut self) {
+		self.proofs.clear();
+		self.uses.clear();
+	}
+}
+
+fn key_of(indices: &[u32]) -> Vec<u32> {
+	let mut key = indices.to_vec();
+
+	key.sort_unstable_by(|a, b| b.cmp(a));
+	key.dedup();
+
+	key
+}
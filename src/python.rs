@@ -0,0 +1,117 @@
+//! Python bindings, with the same keccak256 rules as the on-chain verifier.
+//!
+//! Build the extension module with:
+//! ```text
+//! cargo rustc --release --features python,pyo3/extension-module --crate-type cdylib
+//! ```
+//! then rename the library to `tiny_sparse_merkle_tree.so`.
+//!
+//! ```python
+//! import tiny_sparse_merkle_tree as smt
+//!
+//! root = smt.build_root(leaves)
+//! proof = smt.get_proof(leaves, [0, 2], "abi")
+//! assert smt.verify(proof, "abi")
+//! ```
+
+// --- alloc ---
+use alloc::{format, vec::Vec};
+// --- crates.io ---
+use pyo3::{exceptions::PyValueError, prelude::*, types::PyBytes};
+// --- sparse-merkle-tree ---
+use crate::{
+	codec::{Abi, Compact, CompactBe, ProofCodec, Scale},
+	hash::Keccak256,
+	Proof, SparseMerkleTree,
+};
+
+type Hash = [u8; 32];
+type Tree = SparseMerkleTree<Hash, Keccak256>;
+
+fn to_tree(leaves: Vec<Vec<u8>>) -> PyResult<Tree> {
+	let leaves = leaves
+		.into_iter()
+		.map(|leaf| {
+			Hash::try_from(leaf.as_slice())
+				.map_err(|_| PyValueError::new_err("the leaf must be 32 bytes"))
+		})
+		.collect::<PyResult<Vec<_>>>()?;
+
+	Ok(Tree::new(leaves))
+}
+
+fn encode(proof: &Proof<Hash, Keccak256>, format: &str) -> PyResult<Vec<u8>> {
+	match format {
+		"compact" => Ok(Compact::encode(proof)),
+		"compact-be" => Ok(CompactBe::encode(proof)),
+		"scale" => Ok(Scale::encode(proof)),
+		"abi" => Ok(Abi::encode(proof)),
+		_ => Err(PyValueError::new_err(format!(
+			"unsupported format `{}`",
+			format
+		))),
+	}
+}
+
+fn decode(data: &[u8], format: &str) -> PyResult<Proof<Hash, Keccak256>> {
+	match format {
+		"compact" => Compact::decode(data),
+		"compact-be" => CompactBe::decode(data),
+		"scale" => Scale::decode(data),
+		"abi" => Abi::decode(data),
+		_ => {
+			return Err(PyValueError::new_err(format!(
+				"unsupported format `{}`",
+				format
+			)))
+		}
+	}
+	.map_err(|e| PyValueError::new_err(format!("invalid proof: {:?}", e)))
+}
+
+/// `build_root(leaves: list[bytes]) -> bytes`
+#[pyfunction]
+fn build_root(py: Python, leaves: Vec<Vec<u8>>) -> PyResult<Bound<PyBytes>> {
+	Ok(PyBytes::new(py, &to_tree(leaves)?.root()))
+}
+
+/// `get_proof(leaves: list[bytes], indices: list[int], format: str = "compact") -> bytes`
+#[pyfunction]
+#[pyo3(signature = (leaves, indices, format = "compact"))]
+fn get_proof<'py>(
+	py: Python<'py>,
+	leaves: Vec<Vec<u8>>,
+	mut indices: Vec<u32>,
+	format: &str,
+) -> PyResult<Bound<'py, PyBytes>> {
+	let tree = to_tree(leaves)?;
+
+	if indices.is_empty() {
+		return Err(PyValueError::new_err("no index"));
+	}
+	if indices.iter().any(|i| *i >= tree.non_empty_leaves_count()) {
+		return Err(PyValueError::new_err("index out of bounds"));
+	}
+
+	// The verification requires the descending order.
+	indices.sort_unstable_by(|a, b| b.cmp(a));
+	indices.dedup();
+
+	Ok(PyBytes::new(py, &encode(&tree.proof_of(indices), format)?))
+}
+
+/// `verify(proof: bytes, format: str = "compact") -> bool`
+#[pyfunction]
+#[pyo3(signature = (proof, format = "compact"))]
+fn verify(proof: &[u8], format: &str) -> PyResult<bool> {
+	Ok(Tree::verify(decode(proof, format)?))
+}
+
+#[pymodule]
+fn tiny_sparse_merkle_tree(m: &Bound<PyModule>) -> PyResult<()> {
+	m.add_function(wrap_pyfunction!(build_root, m)?)?;
+	m.add_function(wrap_pyfunction!(get_proof, m)?)?;
+	m.add_function(wrap_pyfunction!(verify, m)?)?;
+
+	Ok(())
+}
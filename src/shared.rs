@@ -0,0 +1,85 @@
+//! Store the large nodes behind [`Arc`], so cloning one is only a reference count bump.
+//!
+//! ```ignore
+//! let tree = SparseMerkleTree::<_, Shared<Blake2b512>>::new(leaves.map(Arc::new));
+//! let proof: Proof<[u8; 64], Blake2b512> = tree.proof_of([0]).into_owned();
+//! ```
+
+// --- core ---
+use core::marker::PhantomData;
+// --- alloc ---
+use alloc::sync::Arc;
+// --- sparse-merkle-tree ---
+use crate::*;
+
+/// Turn `M` into a merger of the [`Arc`]ed nodes, the roots and the proofs are the same.
+///
+/// The merge result is written in place if the output node isn't shared.
+pub struct Shared<M>(PhantomData<M>);
+impl<M> Merge for Shared<M>
+where
+	M: Merge,
+{
+	type Item = Arc<M::Item>;
+
+	fn empty() -> Self::Item {
+		Arc::new(M::empty())
+	}
+
+	fn merge(l: &Self::Item, r: &Self::Item) -> Self::Item {
+		Arc::new(M::merge(l, r))
+	}
+
+	fn merge_into(out: &mut Self::Item, l: &Self::Item, r: &Self::Item) {
+		match Arc::get_mut(out) {
+			Some(out) => M::merge_into(out, l, r),
+			None => *out = Self::merge(l, r),
+		}
+	}
+}
+impl<M> MergeCount for Shared<M>
+where
+	M: MergeCount,
+{
+	fn count(non_empty_leaves_count: u32) -> Self::Item {
+		Arc::new(M::count(non_empty_leaves_count))
+	}
+}
+
+impl<H, M> Proof<Arc<H>, Shared<M>>
+where
+	H: Clone,
+{
+	/// Unwrap the nodes, e.g. to encode the proof.
+	pub fn into_owned(self) -> Proof<H, M> {
+		let unwrap = |node: Arc<H>| Arc::try_unwrap(node).unwrap_or_else(|node| (*node).clone());
+
+		Proof {
+			root: unwrap(self.root),
+			leaves_with_index: self
+				.leaves_with_index
+				.into_iter()
+				.map(|(i, leaf)| (i, unwrap(leaf)))
+				.collect(),
+			proof: self.proof.into_iter().map(unwrap).collect(),
+			non_empty_leaves_count: self.non_empty_leaves_count,
+			_merge: PhantomData,
+		}
+	}
+}
+impl<H, M> Proof<H, M> {
+	/// Wrap the nodes, e.g. to verify a decoded proof with [`Shared`].
+	pub fn into_shared(self) -> Proof<Arc<H>, Shared<M>> {
+		Proof {
+			root: Arc::new(self.root),
+			leaves_with_index: self
+				.leaves_with_index
+				.into_iter()
+				.map(|(i, leaf)| (i, Arc::new(leaf)))
+				.collect(),
+			proof: self.proof.into_iter().map(Arc::new).collect(),
+			non_empty_leaves_count: self.non_empty_leaves_count,
+			_merge: PhantomData,
+		}
+	}
+}
@@ -0,0 +1,134 @@
+//! Generate a Solidity verifier of the [`Abi`](crate::codec::Abi) encoded multiproofs.
+//!
+//! The generated library hashes with `keccak256`, the same as
+//! [`Keccak256`](crate::hash::Keccak256), and runs the same loop as
+//! [`SparseMerkleTree::verify`](crate::SparseMerkleTree::verify).
+
+// --- alloc ---
+use alloc::string::String;
+
+const TEMPLATE: &str = r#"// SPDX-License-Identifier: GPL-3.0
+// Generated by tiny-sparse-merkle-tree, do not edit.
+pragma solidity ^0.8.0;
+
+/// Verify the multiproofs of tiny-sparse-merkle-tree, with the `keccak256` merger.
+///
+/// A non-zero `leavesCount` means the proof is against the committed root,
+/// `keccak256(root, keccak256(uint32(leavesCount)))`.
+library __LIBRARY__ {
+    /// `proof` is `abi.encode(root, leavesCount, indices, leaves, siblings)`.
+    function verify(bytes memory proof) internal pure returns (bool) {
+        (
+            bytes32 root,
+            uint32 leavesCount,
+            uint32[] memory indices,
+            bytes32[] memory leaves,
+            bytes32[] memory siblings
+        ) = abi.decode(proof, (bytes32, uint32, uint32[], bytes32[], bytes32[]));
+
+        return verify(root, leavesCount, indices, leaves, siblings);
+    }
+
+    /// The indices are the node indices, in the descending order.
+    function verify(
+        bytes32 root,
+        uint32 leavesCount,
+        uint32[] memory indices,
+        bytes32[] memory leaves,
+        bytes32[] memory siblings
+    ) internal pure returns (bool) {
+        uint256 n = indices.length;
+
+        if (n == 0 || n != leaves.length) {
+            return false;
+        }
+
+        if (leavesCount != 0) {
+            uint256 halfLeavesCount = nextPowerOfTwo(leavesCount);
+
+            for (uint256 k = 0; k < n; k++) {
+                if (indices[k] < halfLeavesCount || indices[k] - halfLeavesCount >= leavesCount) {
+                    return false;
+                }
+            }
+        }
+
+        // A ring buffer, never holds more than `n` nodes.
+        uint256[] memory queueIndices = new uint256[](n);
+        bytes32[] memory queueNodes = new bytes32[](n);
+        uint256 head = 0;
+        uint256 len = n;
+        uint256 p = 0;
+
+        for (uint256 k = 0; k < n; k++) {
+            queueIndices[k] = indices[k];
+            queueNodes[k] = leaves[k];
+        }
+
+        while (len > 0) {
+            uint256 i = queueIndices[head];
+            bytes32 node = queueNodes[head];
+
+            head = (head + 1) % n;
+            len--;
+
+            if (i == 1) {
+                if (leavesCount != 0) {
+                    node = merge(node, keccak256(abi.encodePacked(leavesCount)));
+                }
+
+                return node == root;
+            }
+
+            bytes32 parent;
+
+            if ((i & 1) == 0) {
+                if (p >= siblings.length) {
+                    return false;
+                }
+
+                parent = merge(node, siblings[p++]);
+            } else if (len > 0 && queueIndices[head] == i - 1) {
+                parent = merge(queueNodes[head], node);
+                head = (head + 1) % n;
+                len--;
+            } else {
+                if (p >= siblings.length) {
+                    return false;
+                }
+
+                parent = merge(siblings[p++], node);
+            }
+
+            uint256 tail = (head + len) % n;
+
+            queueIndices[tail] = i / 2;
+            queueNodes[tail] = parent;
+            len++;
+        }
+
+        return false;
+    }
+
+    function merge(bytes32 l, bytes32 r) private pure returns (bytes32) {
+        return keccak256(abi.encodePacked(l, r));
+    }
+
+    function nextPowerOfTwo(uint256 x) private pure returns (uint256) {
+        uint256 y = 1;
+
+        while (y < x) {
+            y <<= 1;
+        }
+
+        return y;
+    }
+}
+"#;
+
+/// Emit a self-contained Solidity library named `library_name`.
+///
+/// `library_name` must be a valid Solidity identifier, it's not checked.
+pub fn verifier(library_name: &str) -> String {
+	TEMPLATE.replace("__LIBRARY__", library_name)
+}
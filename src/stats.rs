@@ -0,0 +1,73 @@
+//! Measure the proofs before submitting them, e.g. to estimate the gas or the weight and to pick
+//! the cheapest format.
+
+// --- sparse-merkle-tree ---
+#[cfg(feature = "cbor")]
+use crate::codec::Cbor;
+use crate::{
+	codec::{Abi, Compact, LittleEndian, ProofCodec, Scale},
+	*,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProofStats {
+	pub siblings_count: usize,
+	/// The depth of the proven leaves, `None` if there isn't any leaf.
+	pub depth: Option<u32>,
+	/// The siblings which are the roots of the empty subtrees, see [`DefaultNodes`].
+	///
+	/// They could be elided by a verifier which knows the default nodes.
+	pub default_siblings_count: usize,
+	pub encoded_sizes: EncodedSizes,
+}
+
+/// The encoded size in bytes of each [`codec`] format.
+///
+/// The big-endian variants are in the same size as the little-endian ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EncodedSizes {
+	pub scale: usize,
+	pub abi: usize,
+	pub compact: usize,
+	/// `None` if the proof can't be compressed, see [`Proof::compress`].
+	pub compressed: Option<usize>,
+	/// `None` if the `cbor` feature is disabled.
+	pub cbor: Option<usize>,
+}
+
+impl<H, M> Proof<H, M>
+where
+	H: AsMut<[u8]> + AsRef<[u8]> + Clone + Default + MaybeDebug + PartialEq,
+	M: Merge<Item = H>,
+{
+	/// Encode the proof in every format to measure it, no verification is done.
+	pub fn stats(&self) -> ProofStats {
+		let depth = self.depth();
+		let default_nodes = DefaultNodes::<H, M>::new(depth.unwrap_or_default());
+		let default_siblings_count = self
+			.proof
+			.iter()
+			.filter(|sibling| {
+				(0..=default_nodes.max_height()).any(|h| default_nodes.get(h) == Some(sibling))
+			})
+			.count();
+
+		ProofStats {
+			siblings_count: self.proof.len(),
+			depth,
+			default_siblings_count,
+			encoded_sizes: EncodedSizes {
+				scale: <Scale as ProofCodec<H>>::encode(self).len(),
+				abi: <Abi as ProofCodec<H>>::encode(self).len(),
+				compact: <Compact as ProofCodec<H>>::encode(self).len(),
+				compressed: self
+					.compress()
+					.map(|proof| proof.encode::<LittleEndian>().len()),
+				#[cfg(feature = "cbor")]
+				cbor: Some(<Cbor as ProofCodec<H>>::encode(self).len()),
+				#[cfg(not(feature = "cbor"))]
+				cbor: None,
+			},
+		}
+	}
+}
@@ -0,0 +1,118 @@
+//! [`proptest`] strategies to property-test the verifiers against this library.
+//!
+//! ```ignore
+//! proptest! {
+//!     #[test]
+//!     fn verifier_should_agree(case in valid_proof::<_, Keccak256, _>(any::<[u8; 32]>(), 64)) {
+//!         prop_assert!(my_verifier(&Abi::encode(&case.proof)));
+//!     }
+//! }
+//! ```
+
+// --- core ---
+use core::fmt::{Debug, Formatter, Result};
+// --- alloc ---
+use alloc::vec::Vec;
+// --- crates.io ---
+use proptest::{
+	arbitrary::any,
+	collection::vec,
+	sample::subsequence,
+	strategy::{Just, Strategy},
+};
+// --- sparse-merkle-tree ---
+use crate::*;
+
+/// A tree, the proven leaf indices and the proof.
+///
+/// The indices are in the descending order.
+pub struct ProofCase<H, M> {
+	pub tree: SparseMerkleTree<H, M>,
+	pub indices: Vec<u32>,
+	pub proof: Proof<H, M>,
+}
+// Implement this manually, the derived one would require `M` to implement it too.
+impl<H, M> Debug for ProofCase<H, M>
+where
+	H: Debug,
+{
+	fn fmt(&self, f: &mut Formatter) -> Result {
+		f.debug_struct("ProofCase")
+			.field("nodes", &self.tree.nodes)
+			.field("indices", &self.indices)
+			.field("root", &self.proof.root)
+			.field("leaves_with_index", &self.proof.leaves_with_index)
+			.field("proof", &self.proof.proof)
+			.finish()
+	}
+}
+
+/// A tree of `1..=max_leaves` leaves from `leaf`, and a valid proof of some of them.
+pub fn valid_proof<H, M, S>(leaf: S, max_leaves: usize) -> impl Strategy<Value = ProofCase<H, M>>
+where
+	H: Clone + Debug + PartialEq,
+	M: Merge<Item = H>,
+	S: Strategy<Value = H>,
+{
+	vec(leaf, 1..=max_leaves.max(1))
+		.prop_flat_map(|leaves| {
+			let n = leaves.len();
+
+			(
+				Just(leaves),
+				subsequence((0..n as u32).collect::<Vec<_>>(), 1..=n),
+			)
+		})
+		.prop_map(|(leaves, mut indices)| {
+			indices.reverse();
+
+			let tree = SparseMerkleTree::new(leaves);
+			let proof = tree.proof_of(&indices);
+
+			ProofCase {
+				tree,
+				indices,
+				proof,
+			}
+		})
+}
+
+/// Same as [`valid_proof`], but the proof is mutated and never verified.
+///
+/// The root, a leaf, a sibling or a leaf index is replaced, or a sibling is removed.
+pub fn invalid_proof<H, M, S>(leaf: S, max_leaves: usize) -> impl Strategy<Value = ProofCase<H, M>>
+where
+	H: Clone + Debug + PartialEq,
+	M: Merge<Item = H>,
+	S: Clone + Strategy<Value = H>,
+{
+	(
+		valid_proof(leaf.clone(), max_leaves),
+		leaf,
+		0..5_u8,
+		any::<usize>(),
+	)
+		.prop_map(|(mut case, replacement, mutation, k)| {
+			mutate(&mut case.proof, replacement, mutation, k);
+
+			case
+		})
+		.prop_filter("the mutation didn't break the proof", |case| {
+			!SparseMerkleTree::verify(case.proof.clone())
+		})
+}
+
+fn mutate<H, M>(proof: &mut Proof<H, M>, replacement: H, mutation: u8, k: usize) {
+	let leaves_count = proof.leaves_with_index.len();
+	let proof_size = proof.proof.len();
+
+	match mutation {
+		1 => proof.leaves_with_index[k % leaves_count].1 = replacement,
+		2 if proof_size != 0 => proof.proof[k % proof_size] = replacement,
+		3 if proof_size != 0 => {
+			proof.proof.remove(k % proof_size);
+		}
+		4 => proof.leaves_with_index[k % leaves_count].0 ^= 1,
+		_ => proof.root = replacement,
+	}
+}
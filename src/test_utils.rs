@@ -0,0 +1,107 @@
+//! Break the valid proofs in the deterministic ways, for the verifier tests and the fuzzers.
+//!
+//! Each helper takes the position to corrupt modulo the length, so any `usize` from a fuzzer input
+//! is accepted. It returns `false` and leaves the proof untouched, if there is nothing to corrupt.
+//!
+//! The corrupted proof is expected to fail the verification, but it's not checked. For example,
+//! swapping two equal leaves changes nothing.
+//!
+//! [`SparseMerkleTree::random`] builds the reproducible trees, for the differential tests against
+//! the other implementations.
+
+// --- crates.io ---
+use rand_core::Rng;
+// --- sparse-merkle-tree ---
+use crate::*;
+
+impl<H, M> SparseMerkleTree<H, M>
+where
+	H: AsMut<[u8]> + Clone + Default + MaybeDebug + PartialEq,
+	M: Merge<Item = H>,
+{
+	/// A tree of `leaf_count` pseudo-random leaves.
+	///
+	/// Each leaf is `H::default()` filled by [`Rng::fill_bytes`], in the index order. So the same
+	/// seeded generator always builds the same tree, any port could replay it with the same
+	/// generator.
+	pub fn random<R>(rng: &mut R, leaf_count: u32) -> Self
+	where
+		R: ?Sized + Rng,
+	{
+		Self::new((0..leaf_count).map(|_| {
+			let mut leaf = H::default();
+
+			rng.fill_bytes(leaf.as_mut());
+
+			leaf
+		}))
+	}
+}
+
+impl<H, M> Proof<H, M> {
+	/// Flip the bit `bit` of the sibling `k`.
+	pub fn corrupt_flip_sibling(&mut self, k: usize, bit: usize) -> bool
+	where
+		H: AsMut<[u8]>,
+	{
+		let Some(sibling) = nth_mut(&mut self.proof, k) else {
+			return false;
+		};
+		let bytes = sibling.as_mut();
+
+		if bytes.is_empty() {
+			return false;
+		}
+
+		let bit = bit % (bytes.len() * 8);
+
+		bytes[bit / 8] ^= 1 << (bit % 8);
+
+		true
+	}
+
+	/// Remove the sibling `k`.
+	pub fn corrupt_drop_sibling(&mut self, k: usize) -> bool {
+		if self.proof.is_empty() {
+			return false;
+		}
+
+		let k = k % self.proof.len();
+
+		self.proof.remove(k);
+
+		true
+	}
+
+	/// Swap the leaf `k` with the next one, the last leaf is swapped with the first one.
+	pub fn corrupt_swap_leaves(&mut self, k: usize) -> bool {
+		let leaves_count = self.leaves_with_index.len();
+
+		if leaves_count < 2 {
+			return false;
+		}
+
+		let k = k % leaves_count;
+
+		self.leaves_with_index.swap(k, (k + 1) % leaves_count);
+
+		true
+	}
+
+	/// Substitute the root, e.g. with the root of another tree.
+	pub fn corrupt_root(&mut self, root: H) -> bool {
+		self.root = root;
+
+		true
+	}
+}
+
+fn nth_mut<T>(items: &mut [T], k: usize) -> Option<&mut T> {
+	let len = items.len();
+
+	if len == 0 {
+		None
+	} else {
+		items.get_mut(k % len)
+	}
+}
@@ -0,0 +1,278 @@
+//! Machine-readable fixtures for the verifiers in the other languages.
+//!
+//! ```json
+//! {
+//!   "hasher": "keccak256",
+//!   "indices": [12, 10],
+//!   "leaves": ["0x01..", "0x02..", "0x03..", "0x04..", "0x05.."],
+//!   "non_empty_leaves_count": null,
+//!   "proven_leaves": ["0x05..", "0x03.."],
+//!   "root": "0x..",
+//!   "siblings": ["0x..", "0x.."]
+//! }
+//! ```
+//!
+//! The indices are the node indices, in the same order as the proven leaves. The leaves are only
+//! present if the vector is exported from a tree. The keys are sorted, the hashes are `0x`
+//! prefixed lowercase hex strings.
+
+// --- core ---
+use core::marker::PhantomData;
+// --- alloc ---
+use alloc::{
+	format,
+	string::{String, ToString},
+	vec::Vec,
+};
+// --- crates.io ---
+use serde_json::{Map, Value};
+// --- sparse-merkle-tree ---
+use crate::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+	/// The input isn't a JSON object.
+	Json,
+	/// The field is missing or malformed.
+	InvalidField(&'static str),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TestVector<H> {
+	pub hasher: String,
+	/// All the leaves of the tree, `None` if exported from a proof.
+	pub leaves: Option<Vec<H>>,
+	pub root: H,
+	pub indices: Vec<u32>,
+	pub proven_leaves: Vec<H>,
+	pub siblings: Vec<H>,
+	/// Only present in the proofs of the committed root.
+	pub non_empty_leaves_count: Option<u32>,
+}
+impl<H> TestVector<H>
+where
+	H: AsMut<[u8]> + AsRef<[u8]> + Default,
+{
+	pub fn from_json(json: &str) -> Result<Self, Error> {
+		let value = serde_json::from_str::<Value>(json).map_err(|_| Error::Json)?;
+		let object = value.as_object().ok_or(Error::Json)?;
+		let hashes = |key| {
+			object
+				.get(key)
+				.and_then(Value::as_array)
+				.and_then(|hashes| {
+					hashes
+						.iter()
+						.map(|hash| hash.as_str().and_then(decode_hash))
+						.collect::<Option<Vec<_>>>()
+				})
+				.ok_or(Error::InvalidField(key))
+		};
+
+		Ok(Self {
+			hasher: object
+				.get("hasher")
+				.and_then(Value::as_str)
+				.ok_or(Error::InvalidField("hasher"))?
+				.into(),
+			leaves: match object.get("leaves") {
+				None | Some(Value::Null) => None,
+				Some(_) => Some(hashes("leaves")?),
+			},
+			root: object
+				.get("root")
+				.and_then(Value::as_str)
+				.and_then(decode_hash)
+				.ok_or(Error::InvalidField("root"))?,
+			indices: object
+				.get("indices")
+				.and_then(Value::as_array)
+				.and_then(|indices| {
+					indices
+						.iter()
+						.map(|i| i.as_u64().and_then(|i| u32::try_from(i).ok()))
+						.collect::<Option<Vec<_>>>()
+				})
+				.ok_or(Error::InvalidField("indices"))?,
+			proven_leaves: hashes("proven_leaves")?,
+			siblings: hashes("siblings")?,
+			non_empty_leaves_count: match object.get("non_empty_leaves_count") {
+				None | Some(Value::Null) => None,
+				Some(n) => Some(
+					n.as_u64()
+						.and_then(|n| u32::try_from(n).ok())
+						.ok_or(Error::InvalidField("non_empty_leaves_count"))?,
+				),
+			},
+		})
+	}
+
+	pub fn to_json(&self) -> String {
+		let hashes =
+			|hashes: &[H]| Value::Array(hashes.iter().map(|h| encode_hash(h).into()).collect());
+		let mut object = Map::new();
+
+		object.insert("hasher".into(), self.hasher.clone().into());
+		object.insert("indices".into(), self.indices.clone().into());
+		object.insert(
+			"leaves".into(),
+			self.leaves.as_deref().map_or(Value::Null, hashes),
+		);
+		object.insert(
+			"non_empty_leaves_count".into(),
+			self.non_empty_leaves_count.into(),
+		);
+		object.insert("proven_leaves".into(), hashes(&self.proven_leaves));
+		object.insert("root".into(), encode_hash(&self.root).into());
+		object.insert("siblings".into(), hashes(&self.siblings));
+
+		serde_json::to_string_pretty(&Value::Object(object))
+			.expect("a `Value` is always serializable; qed")
+	}
+}
+impl<H> TestVector<H>
+where
+	H: Clone,
+{
+	pub fn to_proof<M>(&self) -> Option<Proof<H, M>> {
+		if self.indices.len() != self.proven_leaves.len() {
+			return None;
+		}
+
+		Some(Proof {
+			root: self.root.clone(),
+			leaves_with_index: self
+				.indices
+				.iter()
+				.copied()
+				.zip(self.proven_leaves.iter().cloned())
+				.collect(),
+			proof: self.siblings.clone(),
+			non_empty_leaves_count: self.non_empty_leaves_count,
+			_merge: PhantomData,
+		})
+	}
+}
+impl<H> TestVector<H>
+where
+	H: Clone + MaybeDebug + PartialEq,
+{
+	/// Verify the proof, and that the leaves if any rebuild the same tree.
+	///
+	/// Use [`TestVector::to_proof`] with [`SparseMerkleTree::verify_committed`] for the proofs of
+	/// the committed root.
+	pub fn verify<M>(&self) -> bool
+	where
+		M: Merge<Item = H>,
+	{
+		if self.non_empty_leaves_count.is_some() {
+			return false;
+		}
+		if let Some(leaves) = &self.leaves {
+			let tree = SparseMerkleTree::<H, M>::new(leaves.iter().cloned());
+			let capacity = tree.capacity();
+
+			if tree.root() != self.root
+				|| self
+					.indices
+					.iter()
+					.zip(&self.proven_leaves)
+					.any(|(i, leaf)| {
+						i.checked_sub(capacity).and_then(|i| leaves.get(i as usize)) != Some(leaf)
+					}) {
+				return false;
+			}
+		}
+
+		self.to_proof::<M>()
+			.map(SparseMerkleTree::verify)
+			.unwrap_or_default()
+	}
+}
+
+impl<H, M> Proof<H, M>
+where
+	H: AsMut<[u8]> + AsRef<[u8]> + Clone + Default,
+{
+	/// Export the proof as a [`TestVector`] JSON, `hasher` is an identifier for the consumers.
+	pub fn to_test_vector(&self, hasher: &str) -> String {
+		TestVector {
+			hasher: hasher.to_string(),
+			leaves: None,
+			root: self.root.clone(),
+			indices: self.leaves_with_index.iter().map(|(i, _)| *i).collect(),
+			proven_leaves: self
+				.leaves_with_index
+				.iter()
+				.map(|(_, leaf)| leaf.clone())
+				.collect(),
+			siblings: self.proof.clone(),
+			non_empty_leaves_count: self.non_empty_leaves_count,
+		}
+		.to_json()
+	}
+}
+
+impl<H, M> SparseMerkleTree<H, M>
+where
+	H: AsMut<[u8]> + AsRef<[u8]> + Clone + Default + MaybeDebug + PartialEq,
+	M: Merge<Item = H>,
+{
+	/// Export the leaves and the proof of `indices` as a [`TestVector`] JSON.
+	pub fn to_test_vector<I>(&self, hasher: &str, indices: I) -> String
+	where
+		I: IntoIterator,
+		I::Item: Borrow<u32>,
+	{
+		let proof = self.proof_of(indices);
+		let capacity = self.capacity() as usize;
+		let leaves = self.nodes[capacity..capacity + self.non_empty_leaves_count as usize].to_vec();
+
+		TestVector {
+			hasher: hasher.to_string(),
+			leaves: Some(leaves),
+			root: proof.root,
+			indices: proof.leaves_with_index.iter().map(|(i, _)| *i).collect(),
+			proven_leaves: proof
+				.leaves_with_index
+				.into_iter()
+				.map(|(_, leaf)| leaf)
+				.collect(),
+			siblings: proof.proof,
+			non_empty_leaves_count: None,
+		}
+		.to_json()
+	}
+}
+
+fn encode_hash<H>(hash: &H) -> String
+where
+	H: AsRef<[u8]>,
+{
+	let mut hex = String::with_capacity(2 + hash.as_ref().len() * 2);
+
+	hex.push_str("0x");
+	hash.as_ref()
+		.iter()
+		.for_each(|byte| hex.push_str(&format!("{:02x}", byte)));
+
+	hex
+}
+
+fn decode_hash<H>(hex: &str) -> Option<H>
+where
+	H: AsMut<[u8]> + Default,
+{
+	let digits = hex.strip_prefix("0x")?.as_bytes();
+	let mut hash = H::default();
+
+	if digits.len() != hash.as_mut().len() * 2 {
+		return None;
+	}
+
+	for (byte, pair) in hash.as_mut().iter_mut().zip(digits.chunks(2)) {
+		*byte = u8::from_str_radix(core::str::from_utf8(pair).ok()?, 16).ok()?;
+	}
+
+	Some(hash)
+}
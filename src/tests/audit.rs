@@ -0,0 +1,107 @@
+// --- alloc ---
+use alloc::{sync::Arc, vec::Vec};
+// --- std ---
+use std::sync::Mutex;
+// --- sparse-merkle-tree ---
+use crate::{audit::AuditLog, hash::test::*, proof_cache::ProofCache, *};
+
+#[derive(Debug, Default)]
+struct Journal(Mutex<Vec<(Vec<u32>, u32, u32)>>);
+impl Journal {
+	fn entries(&self) -> Vec<(Vec<u32>, u32, u32)> {
+		self.0.lock().unwrap().clone()
+	}
+}
+impl AuditLog<u32, DebugView> for Journal {
+	fn on_proof(&self, indices: &[u32], proof: &Proof<u32, DebugView>) {
+		self.0
+			.lock()
+			.unwrap()
+			.push((indices.to_vec(), proof.root, proof.digest()));
+	}
+
+	fn on_update(&self, indices: &[u32], root_before: &u32, root_after: &u32) {
+		self.0
+			.lock()
+			.unwrap()
+			.push((indices.to_vec(), *root_before, *root_after));
+	}
+}
+
+#[test]
+fn audit_log_should_work() {
+	let journal = Arc::new(Journal::default());
+	let mut smt = SparseMerkleTree::<_, DebugView>::new(1..=4);
+
+	smt.set_audit_log(journal.clone());
+
+	// 10 + 2 + 5 + 2 + 4 + 1 + 1 + 7 + 0
+	let proof = smt.proof_of([1, 0]);
+
+	assert_eq!(proof.digest(), 32);
+
+	smt.update_leaf(3, 10).unwrap();
+	assert!(smt.update_leaf(4, 10).is_err());
+	// Past the capacity, the tree is rebuilt and keeps the log.
+	assert_eq!(smt.push_leaf(6), 4);
+	assert!(smt.audit_log().is_some());
+	smt.push_leaf(1);
+
+	assert_eq!(
+		journal.entries(),
+		[
+			([1, 0].to_vec(), 10, 32),
+			([3].to_vec(), 10, 16),
+			([4].to_vec(), 16, 22),
+			([5].to_vec(), 22, 23),
+		]
+	);
+
+	let committed_proof = smt.committed_proof_of([0]);
+	let (indices, root, digest) = journal.entries().pop().unwrap();
+
+	assert_eq!(indices, [0]);
+	assert_eq!(root, committed_proof.root);
+	assert_eq!(digest, committed_proof.digest());
+
+	assert!(smt.take_audit_log().is_some());
+	smt.proof_of([0]);
+	assert_eq!(journal.entries().len(), 5);
+
+	// `()` ignores everything.
+	smt.set_audit_log(Arc::new(()));
+	smt.update_leaf(0, 1).unwrap();
+}
+
+#[test]
+fn proof_cache_hits_should_be_audited() {
+	let journal = Arc::new(Journal::default());
+	let mut smt = SparseMerkleTree::<_, DebugView>::new(1..=4);
+
+	smt.set_audit_log(journal.clone());
+
+	let mut cache = ProofCache::new(smt, 1);
+
+	cache.proof_of([0, 1]);
+	cache.proof_of([1, 0]);
+
+	let entries = journal.entries();
+
+	assert_eq!(entries.len(), 2);
+	assert_eq!(entries[0].1, entries[1].1);
+	assert_eq!(entries[0].2, entries[1].2);
+	assert_eq!(entries[1].0, [1, 0]);
+}
+
+#[test]
+fn digest_should_commit_to_the_indices_and_the_count() {
+	let smt = SparseMerkleTree::<_, DebugView>::new(1..=4);
+	let proof = smt.proof_of([0]);
+	let digest = proof.digest();
+	let mut moved = proof.clone();
+
+	moved.leaves_with_index[0].0 += 1;
+
+	assert_ne!(moved.digest(), digest);
+	assert_ne!(smt.committed_proof_of([0]).digest(), digest);
+}
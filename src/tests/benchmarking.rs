@@ -0,0 +1,50 @@
+// --- sparse-merkle-tree ---
+use crate::{benchmarking::*, hash::test::*, instrument::MergeCounter, *};
+
+#[test]
+fn worst_case_should_work() {
+	assert_eq!(worst_case_indices(4, 3), [8, 4, 0]);
+	assert_eq!(worst_case_indices(4, 5), [12, 8, 4, 2, 0]);
+	assert_eq!(
+		worst_case_indices(4, 100),
+		(0..16).rev().collect::<Vec<_>>()
+	);
+	assert_eq!(worst_case_indices(0, 0), [0]);
+	// Clamped to `MAX_DEPTH`.
+	assert_eq!(worst_case_indices(u32::BITS, 2), [1 << (MAX_DEPTH - 1), 0]);
+	assert_eq!(
+		worst_case_indices(u32::MAX, 2),
+		worst_case_indices(MAX_DEPTH, 2)
+	);
+
+	let tree = full_tree::<_, CheckMergeOrder, _>(3, |i| i * 7);
+	let mut max_siblings = [0; 9];
+
+	// Every non-empty subset of the leaves.
+	(1_u32..1 << 8).for_each(|subset| {
+		let indices = (0..8)
+			.rev()
+			.filter(|i| subset >> i & 1 == 1)
+			.collect::<Vec<_>>();
+		let siblings = &mut max_siblings[indices.len()];
+
+		*siblings = (*siblings).max(tree.proof_of(indices).proof.len());
+	});
+
+	(1..=8).for_each(|leaves_count| {
+		let (tree, proof) = worst_case::<_, CheckMergeOrder, _>(3, leaves_count, |i| i * 7);
+		let siblings = proof.proof.len();
+		let mut counter = MergeCounter::default();
+
+		assert_eq!(tree.non_empty_leaves_count(), 8);
+		assert_eq!(siblings, max_siblings[leaves_count as usize]);
+		assert!(SparseMerkleTree::verify_with_instrument(
+			proof,
+			&mut counter
+		));
+		assert_eq!(
+			counter.verify as usize,
+			siblings + leaves_count as usize - 1
+		);
+	});
+}
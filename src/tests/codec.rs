@@ -0,0 +1,207 @@
+// --- core ---
+use core::marker::PhantomData;
+// --- sparse-merkle-tree ---
+#[cfg(feature = "cbor")]
+use crate::codec::Cbor;
+use crate::{
+	codec::{Abi, Compact, CompactBe, Endianness, Error, ProofCodec, Scale},
+	hash::test::*,
+	*,
+};
+
+fn debug_bytes_proof() -> Proof<[u8; 4], DebugBytes> {
+	//                15
+	//        0               15
+	//    0       0       10      5
+	//  0   0   0   0   3   7   5   0
+	// 0 0 0 0 0 0 0 0 1 2 3 4 5 0 0 0
+	let smt = SparseMerkleTree::<_, DebugBytes>::new((1..=5_u32).map(|i| i.to_be_bytes()));
+
+	smt.proof_of([4, 2, 0])
+}
+
+#[test]
+fn codecs_should_round_trip() {
+	fn round_trip<C>(proof: &Proof<[u8; 4], DebugBytes>)
+	where
+		C: ProofCodec<[u8; 4]>,
+	{
+		let encoded = C::encode(proof);
+
+		assert!(&C::decode(&encoded).unwrap() == proof);
+		assert!(matches!(
+			C::decode::<DebugBytes>(&encoded[..encoded.len() - 1]),
+			Err(Error::UnexpectedEof)
+		));
+
+		let mut encoded = encoded;
+
+		encoded.push(0);
+
+		assert!(C::decode::<DebugBytes>(&encoded).is_err());
+	}
+
+	let proof = debug_bytes_proof();
+
+	round_trip::<Compact>(&proof);
+	round_trip::<CompactBe>(&proof);
+	round_trip::<Scale>(&proof);
+	round_trip::<Abi>(&proof);
+
+	let committed_proof = SparseMerkleTree::<_, DebugBytes>::new((1..=5_u32).map(u32::to_be_bytes))
+		.committed_proof_of([4, 2, 0]);
+
+	round_trip::<Compact>(&committed_proof);
+	round_trip::<CompactBe>(&committed_proof);
+	round_trip::<Scale>(&committed_proof);
+	round_trip::<Abi>(&committed_proof);
+
+	#[cfg(feature = "cbor")]
+	{
+		round_trip::<Cbor>(&proof);
+		round_trip::<Cbor>(&committed_proof);
+	}
+
+	assert!(SparseMerkleTree::<_, DebugBytes>::verify(
+		Scale::decode(&Scale::encode(&proof)).unwrap()
+	));
+}
+
+#[test]
+fn scale_should_match_the_reference_encoding() {
+	let proof = Proof::<_, DebugBytes> {
+		root: [0, 0, 0, 15],
+		leaves_with_index: [(8, [0, 0, 0, 1])].to_vec(),
+		proof: [[0, 0, 0, 2], [0, 0, 0, 7]].to_vec(),
+		non_empty_leaves_count: Some(1),
+		_merge: PhantomData,
+	};
+
+	assert_eq!(
+		Scale::encode(&proof),
+		[
+			[0, 0, 0, 15].as_ref(),
+			&[1 << 2],
+			&[8, 0, 0, 0, 0, 0, 0, 1],
+			&[2 << 2],
+			&[0, 0, 0, 2, 0, 0, 0, 7],
+			&[1, 1, 0, 0, 0]
+		]
+		.concat()
+	);
+}
+
+#[test]
+fn compact_should_match_the_reference_encoding() {
+	let proof = Proof::<_, DebugBytes> {
+		root: [0, 0, 0, 15],
+		leaves_with_index: [(8, [0, 0, 0, 1])].to_vec(),
+		proof: [[0, 0, 0, 2], [0, 0, 0, 7]].to_vec(),
+		non_empty_leaves_count: Some(1),
+		_merge: PhantomData,
+	};
+	let le = [
+		[0, 0, 0, 15].as_ref(),
+		&[1, 0, 0, 0],
+		&[8, 0, 0, 0, 0, 0, 0, 1],
+		&[2, 0, 0, 0],
+		&[0, 0, 0, 2, 0, 0, 0, 7],
+		&[1, 1, 0, 0, 0],
+	]
+	.concat();
+	let be = [
+		[0, 0, 0, 15].as_ref(),
+		&[0, 0, 0, 1],
+		&[0, 0, 0, 8, 0, 0, 0, 1],
+		&[0, 0, 0, 2],
+		&[0, 0, 0, 2, 0, 0, 0, 7],
+		&[1, 0, 0, 0, 1],
+	]
+	.concat();
+
+	assert_eq!(Compact::encode(&proof), le);
+	assert_eq!(CompactBe::encode(&proof), be);
+	assert!(Compact::decode(&le).unwrap() == proof);
+	assert!(CompactBe::decode(&be).unwrap() == proof);
+	// The other byte order reads a huge leaves count.
+	assert!(matches!(
+		<CompactBe as ProofCodec<[u8; 4]>>::decode::<DebugBytes>(&le),
+		Err(Error::UnexpectedEof)
+	));
+}
+
+#[test]
+fn endianness_should_be_exposed() {
+	fn endianness<C>() -> Endianness
+	where
+		C: ProofCodec<[u8; 4]>,
+	{
+		C::ENDIANNESS
+	}
+
+	assert_eq!(endianness::<Compact>(), Endianness::Little);
+	assert_eq!(endianness::<CompactBe>(), Endianness::Big);
+	assert_eq!(endianness::<Scale>(), Endianness::Little);
+	assert_eq!(endianness::<Abi>(), Endianness::Big);
+	#[cfg(feature = "cbor")]
+	assert_eq!(endianness::<Cbor>(), Endianness::Big);
+
+	assert_eq!(Endianness::Big.encode_u32(0x0102_0304), [1, 2, 3, 4]);
+	assert_eq!(Endianness::Little.encode_u32(0x0102_0304), [4, 3, 2, 1]);
+	[Endianness::Big, Endianness::Little].iter().for_each(|e| {
+		assert_eq!(e.decode_u32(e.encode_u32(0xdead_beef)), 0xdead_beef);
+	});
+}
+
+#[cfg(feature = "cbor")]
+#[test]
+fn cbor_should_match_the_reference_encoding() {
+	let proof = Proof::<_, DebugBytes> {
+		root: [0, 0, 0, 15],
+		leaves_with_index: [(8, [0, 0, 0, 1])].to_vec(),
+		proof: [[0, 0, 0, 2]].to_vec(),
+		non_empty_leaves_count: None,
+		_merge: PhantomData,
+	};
+	// [h'0000000f', [[8, h'00000001']], [h'00000002'], null]
+	let encoded = [
+		[0x84, 0x44, 0, 0, 0, 15].as_ref(),
+		&[0x81, 0x82, 0x08, 0x44, 0, 0, 0, 1],
+		&[0x81, 0x44, 0, 0, 0, 2],
+		&[0xf6],
+	]
+	.concat();
+
+	assert_eq!(Cbor::encode(&proof), encoded);
+	assert!(matches!(
+		<Cbor as ProofCodec<[u8; 4]>>::decode::<DebugBytes>(&[0x84, 0x43, 0, 0, 15]),
+		Err(Error::InvalidLength)
+	));
+	assert!(matches!(
+		<Cbor as ProofCodec<[u8; 4]>>::decode::<DebugBytes>(&[0xa0]),
+		Err(Error::Malformed)
+	));
+}
+
+#[test]
+fn huge_counts_should_be_rejected() {
+	let root = [0_u8; 4];
+	let leaves_count = [root.as_ref(), &[0xff; 4]].concat();
+	let proof_count = [root.as_ref(), &[0; 4], &[0xff; 4]].concat();
+
+	assert!(matches!(
+		<Compact as ProofCodec<[u8; 4]>>::decode::<DebugBytes>(&leaves_count),
+		Err(Error::UnexpectedEof)
+	));
+	assert!(matches!(
+		<Compact as ProofCodec<[u8; 4]>>::decode::<DebugBytes>(&proof_count),
+		Err(Error::UnexpectedEof)
+	));
+	// SCALE compact `u32::MAX`.
+	assert!(matches!(
+		<Scale as ProofCodec<[u8; 4]>>::decode::<DebugBytes>(
+			&[root.as_ref(), &[0b11, 0xff, 0xff, 0xff, 0xff]].concat()
+		),
+		Err(Error::UnexpectedEof)
+	));
+}
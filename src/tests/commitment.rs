@@ -0,0 +1,44 @@
+// --- sparse-merkle-tree ---
+use crate::{
+	commitment::MerkleCommitment,
+	hash::test::*,
+	kary::{Binary, KaryTree},
+	virtual_tree::VirtualTree,
+	*,
+};
+
+fn check<C>(commitment: &C, non_empty_leaves_count: u32)
+where
+	C: MerkleCommitment,
+	C::Proof: Clone,
+{
+	let last = non_empty_leaves_count - 1;
+
+	[[last].as_ref(), &[0, last], &[last, 0]]
+		.iter()
+		.for_each(|indices| {
+			let proof = commitment.prove(indices).unwrap();
+
+			assert!(C::verify(proof));
+		});
+
+	assert!(commitment.prove(&[]).is_none());
+	assert!(commitment.prove(&[non_empty_leaves_count]).is_none());
+	assert!(commitment.prove(&[0, 0]).is_none());
+}
+
+#[test]
+fn commitments_should_be_interchangeable() {
+	let smt = SparseMerkleTree::<_, CheckMergeOrder>::new(1..=9);
+	let virtual_tree = VirtualTree::<_, CheckMergeOrder>::new(1..=9);
+	let kary = KaryTree::<_, Binary<CheckMergeOrder>, 2>::new(1..=9);
+
+	check(&smt, 9);
+	check(&virtual_tree, 9);
+	check(&kary, 9);
+
+	assert_eq!(MerkleCommitment::root(&smt), smt.root());
+	assert_eq!(MerkleCommitment::root(&virtual_tree), smt.root());
+	assert_eq!(MerkleCommitment::root(&kary), smt.root());
+	assert!(smt.prove(&[0, 3]).unwrap() == smt.proof_of([3, 0]));
+}
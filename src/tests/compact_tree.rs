@@ -0,0 +1,48 @@
+// --- sparse-merkle-tree ---
+use crate::{hash::test::*, *};
+
+#[test]
+fn finalize_should_work() {
+	for n in 1..=9 {
+		let smt = SparseMerkleTree::<u32, CheckMergeOrder>::new(1..=n);
+		let root = smt.root();
+		let capacity = smt.capacity();
+		let compact_tree = smt.finalize();
+
+		assert_eq!(compact_tree.root(), root);
+		assert!(compact_tree.non_empty_nodes_count() * compact_tree.leaves_per_node() >= n);
+
+		for i in 0..compact_tree.non_empty_nodes_count() {
+			let l = i * 2 + 1;
+
+			if capacity > 1 {
+				let r = if l < n { l + 1 } else { 0 };
+
+				assert_eq!(compact_tree.node(i), Some(&CheckMergeOrder::merge(&l, &r)));
+			} else {
+				assert_eq!(compact_tree.node(i), Some(&1));
+			}
+		}
+
+		let indices = (0..compact_tree.non_empty_nodes_count())
+			.rev()
+			.collect::<Vec<_>>();
+		let proof = compact_tree.proof_of(&indices);
+
+		assert_eq!(proof.validate_structure(), Ok(()));
+		assert!(SparseMerkleTree::<u32, CheckMergeOrder>::verify(proof));
+		assert!(SparseMerkleTree::<u32, CheckMergeOrder>::verify(
+			compact_tree.proof_of([0])
+		));
+		assert!(compact_tree.proof_of([compact_tree.non_empty_nodes_count()]) == Proof::default());
+		assert_eq!(
+			compact_tree.node(compact_tree.non_empty_nodes_count()),
+			None
+		);
+	}
+
+	assert_eq!(
+		SparseMerkleTree::<u32, DebugView>::new([1, 2, 3].into_iter()).into_root(),
+		6
+	);
+}
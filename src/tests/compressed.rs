@@ -0,0 +1,116 @@
+// --- sparse-merkle-tree ---
+use crate::{
+	codec::{BigEndian, Error, LittleEndian},
+	compressed::CompressedProof,
+	hash::test::*,
+	*,
+};
+
+#[test]
+fn compressed_proof_should_work() {
+	let smt = SparseMerkleTree::<_, DebugBytes>::new((1..=13_u32).map(u32::to_be_bytes));
+	let subtrees = |indices: &[u32]| {
+		smt.compressed_proof_of(indices)
+			.unwrap()
+			.subtrees()
+			.iter()
+			.map(|(i, leaves)| (*i, leaves.len()))
+			.collect::<Vec<_>>()
+	};
+
+	// The first 8 leaves are the left subtree of the root.
+	assert_eq!(subtrees(&(0..8).rev().collect::<Vec<_>>()), [(2, 8)]);
+	assert_eq!(
+		subtrees(&(0..13).collect::<Vec<_>>()),
+		[(2, 8), (6, 4), (28, 1)]
+	);
+	assert_eq!(
+		subtrees(&[1, 2, 3, 4, 5, 6]),
+		[(17, 1), (9, 2), (10, 2), (22, 1)]
+	);
+	assert_eq!(subtrees(&[5, 0, 5]), [(16, 1), (21, 1)]);
+
+	[
+		(0..13).rev().collect::<Vec<_>>(),
+		(0..8).collect(),
+		[1, 2, 3, 4, 5, 6].to_vec(),
+		[12].to_vec(),
+	]
+	.iter()
+	.for_each(|indices| {
+		let compressed = smt.compressed_proof_of(indices).unwrap();
+		let mut sorted = indices.clone();
+
+		sorted.sort_unstable_by(|a, b| b.cmp(a));
+
+		assert!(compressed.clone().decompress().unwrap() == smt.proof_of(&sorted));
+		assert!(SparseMerkleTree::verify_compressed(compressed.clone()));
+
+		let le = compressed.encode::<LittleEndian>();
+		let be = compressed.encode::<BigEndian>();
+
+		assert!(CompressedProof::decode::<LittleEndian>(&le).unwrap() == compressed);
+		assert!(CompressedProof::decode::<BigEndian>(&be).unwrap() == compressed);
+		assert!(matches!(
+			CompressedProof::<[u8; 4], DebugBytes>::decode::<LittleEndian>(&le[..le.len() - 1]),
+			Err(Error::UnexpectedEof)
+		));
+	});
+
+	// Smaller than listing the indices one by one.
+	let dense = smt.compressed_proof_of((0..8).collect::<Vec<_>>()).unwrap();
+
+	assert_eq!(
+		dense.encode::<LittleEndian>().len(),
+		4 + 4 + (4 + 1 + 8 * 4) + 4 + 4 + 1
+	);
+	assert_eq!(
+		<codec::Compact as codec::ProofCodec<_>>::encode(&smt.proof_of((0..8).collect::<Vec<_>>()))
+			.len(),
+		4 + 4 + 8 * (4 + 4) + 4 + 4 + 1
+	);
+
+	// Out of bounds.
+	assert!(smt.compressed_proof_of([13]).is_none());
+
+	// The subtrees `2` and `6` are collapsed, the leaf `28` stays.
+	let proof = smt
+		.compressed_proof_of((0..13).collect::<Vec<_>>())
+		.unwrap();
+	let le = proof.encode::<LittleEndian>();
+	let verify = |encoded: &[u8]| {
+		SparseMerkleTree::verify_compressed(
+			CompressedProof::<[u8; 4], DebugBytes>::decode::<LittleEndian>(encoded).unwrap(),
+		)
+	};
+
+	assert!(verify(&le));
+
+	// A tampered leaf of the subtree `6`, after the root, the subtrees count and the subtree `2`.
+	let mut tampered = le.clone();
+
+	tampered[4 + 4 + (4 + 1 + 8 * 4) + 4 + 1] ^= 1;
+
+	assert!(!verify(&tampered));
+
+	// The subtree `6` is moved into the subtree `2`.
+	let mut overlapping = le.clone();
+
+	overlapping[4 + 4 + (4 + 1 + 8 * 4)] = 4;
+
+	assert!(!verify(&overlapping));
+
+	// The subtree `6` is moved to the leaves depth.
+	let mut misaligned = le;
+
+	misaligned[4 + 4 + (4 + 1 + 8 * 4)] = 24;
+
+	assert!(!verify(&misaligned));
+
+	// A subtree of `2^255` leaves.
+	let mut malformed = dense.encode::<LittleEndian>();
+
+	malformed[12] = 0xff;
+
+	assert!(CompressedProof::<[u8; 4], DebugBytes>::decode::<LittleEndian>(&malformed).is_err());
+}
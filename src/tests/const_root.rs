@@ -0,0 +1,19 @@
+// --- sparse-merkle-tree ---
+use crate::{hash::test::*, *};
+
+const fn check_merge_order(l: &u32, r: &u32) -> u32 {
+	2 * *l + *r
+}
+
+#[test]
+fn const_root_should_work() {
+	const EMPTY: u32 = const_root!(check_merge_order, 0; []);
+	const ONE: u32 = const_root!(check_merge_order, 0; [7]);
+	const NINE: u32 = const_root!(check_merge_order, 0; [1, 2, 3, 4, 5, 6, 7, 8, 9,]);
+
+	let root = |n| SparseMerkleTree::<_, CheckMergeOrder>::new(1..=n).root();
+
+	assert_eq!(EMPTY, root(0));
+	assert_eq!(ONE, 7);
+	assert_eq!(NINE, root(9));
+}
@@ -0,0 +1,53 @@
+// --- alloc ---
+use alloc::boxed::Box;
+// --- sparse-merkle-tree ---
+use crate::{
+	dyn_merge::{Dyn, DynMerge, MergeAdapter},
+	hash::test::*,
+	*,
+};
+
+fn merger_of(name: &str) -> Box<dyn DynMerge<u32>> {
+	match name {
+		"debug-view" => Box::new(MergeAdapter::<DebugView>::new()),
+		_ => Box::new(MergeAdapter::<CheckMergeOrder>::new()),
+	}
+}
+
+#[test]
+fn dyn_merge_should_work() {
+	for n in 1..=9 {
+		let leaves = (1..=n).collect::<Vec<u32>>();
+		let debug_view = merger_of("debug-view");
+		let check_merge_order = merger_of("check-merge-order");
+		let smt = SparseMerkleTree::<u32, Dyn>::new_dyn(leaves.clone(), &*debug_view);
+		let smt_1 = SparseMerkleTree::<u32, Dyn>::new_dyn(leaves.clone(), &*check_merge_order);
+
+		assert_eq!(
+			smt.nodes,
+			SparseMerkleTree::<u32, DebugView>::new(leaves.clone().into_iter()).nodes
+		);
+		assert_eq!(
+			smt_1.nodes,
+			SparseMerkleTree::<u32, CheckMergeOrder>::new(leaves.into_iter()).nodes
+		);
+		assert_eq!(smt_1.root_dyn(&*check_merge_order), smt_1.nodes[1]);
+
+		let indices = (0..n).rev().collect::<Vec<_>>();
+
+		assert!(SparseMerkleTree::verify_dyn(
+			smt_1.proof_of_dyn(&indices, &*check_merge_order),
+			&*check_merge_order
+		));
+		assert!(
+			!SparseMerkleTree::verify_dyn(
+				smt_1.proof_of_dyn(&indices, &*check_merge_order),
+				&*debug_view
+			) || n == 1
+		);
+		assert!(!SparseMerkleTree::verify_dyn(
+			smt.proof_of_dyn([n], &*debug_view),
+			&*debug_view
+		));
+	}
+}
@@ -0,0 +1,201 @@
+// --- alloc ---
+use alloc::{
+	format,
+	string::{String, ToString},
+	vec,
+};
+// --- sparse-merkle-tree ---
+use crate::{
+	foreign::{self, Side, SortedPair},
+	hash::test::*,
+	*,
+};
+
+/// Spell out the tree shape, so any misplaced sibling changes the root.
+struct Shape;
+impl Merge for Shape {
+	type Item = String;
+
+	fn empty() -> Self::Item {
+		String::new()
+	}
+
+	fn merge(l: &Self::Item, r: &Self::Item) -> Self::Item {
+		format!("({l},{r})")
+	}
+}
+
+/// RFC 6962 section 2.1, `MTH`.
+fn rfc6962_root(leaves: &[String]) -> String {
+	if leaves.len() == 1 {
+		return leaves[0].clone();
+	}
+
+	let k = split(leaves.len());
+
+	Shape::merge(&rfc6962_root(&leaves[..k]), &rfc6962_root(&leaves[k..]))
+}
+
+/// RFC 6962 section 2.1.1, `PATH`.
+fn rfc6962_path(m: usize, leaves: &[String]) -> Vec<String> {
+	if leaves.len() == 1 {
+		return Vec::new();
+	}
+
+	let k = split(leaves.len());
+
+	if m < k {
+		let mut path = rfc6962_path(m, &leaves[..k]);
+
+		path.push(rfc6962_root(&leaves[k..]));
+
+		path
+	} else {
+		let mut path = rfc6962_path(m - k, &leaves[k..]);
+
+		path.push(rfc6962_root(&leaves[..k]));
+
+		path
+	}
+}
+
+/// The largest power of two smaller than `n`.
+fn split(n: usize) -> usize {
+	1 << (usize::BITS - 1 - (n - 1).leading_zeros())
+}
+
+#[test]
+fn from_rfc6962_audit_path_should_work() {
+	(1..=17_usize).for_each(|n| {
+		let leaves = (0..n).map(|i| i.to_string()).collect::<Vec<_>>();
+		let root = rfc6962_root(&leaves);
+
+		(0..n).for_each(|m| {
+			let path = rfc6962_path(m, &leaves);
+			let proof = foreign::from_rfc6962_audit_path::<_, Shape>(
+				root.clone(),
+				leaves[m].clone(),
+				m as _,
+				n as _,
+				path.clone(),
+			)
+			.unwrap();
+
+			assert!(SparseMerkleTree::verify(proof));
+
+			// The wrong position.
+			if n > 1 {
+				assert!(foreign::from_rfc6962_audit_path::<_, Shape>(
+					root.clone(),
+					leaves[m].clone(),
+					((m + 1) % n) as _,
+					n as _,
+					path.clone(),
+				)
+				.is_none_or(|proof| !SparseMerkleTree::verify(proof)));
+			}
+
+			// Too long or too short.
+			assert!(foreign::from_rfc6962_audit_path::<_, Shape>(
+				root.clone(),
+				leaves[m].clone(),
+				m as _,
+				n as _,
+				[path.clone(), vec![String::new()]].concat(),
+			)
+			.is_none());
+
+			if !path.is_empty() {
+				assert!(foreign::from_rfc6962_audit_path::<_, Shape>(
+					root.clone(),
+					leaves[m].clone(),
+					m as _,
+					n as _,
+					path[1..].to_vec(),
+				)
+				.is_none());
+			}
+		});
+	});
+
+	assert!(foreign::from_rfc6962_audit_path::<_, Shape>(
+		String::new(),
+		String::new(),
+		1,
+		1,
+		Vec::new()
+	)
+	.is_none());
+}
+
+#[test]
+fn from_path_should_work() {
+	let smt = SparseMerkleTree::<_, Shape>::new((0..6).map(|i: u32| i.to_string()));
+
+	(0..6).for_each(|i| {
+		// As `merkletreejs` outputs.
+		let path = smt
+			.proof_of([i])
+			.proof
+			.into_iter()
+			.enumerate()
+			.map(|(height, sibling)| {
+				let side = if (i >> height) & 1 == 1 {
+					Side::Left
+				} else {
+					Side::Right
+				};
+
+				(side, sibling)
+			})
+			.collect::<Vec<_>>();
+		let proof =
+			foreign::from_path::<_, Shape, _>(smt.root(), i.to_string(), path.clone()).unwrap();
+
+		assert!(proof == smt.proof_of([i]));
+		assert!(SparseMerkleTree::verify(proof));
+
+		// Flip a side.
+		let mut flipped = path;
+
+		flipped[0].0 = if flipped[0].0 == Side::Left {
+			Side::Right
+		} else {
+			Side::Left
+		};
+
+		assert!(!SparseMerkleTree::verify(
+			foreign::from_path::<_, Shape, _>(smt.root(), i.to_string(), flipped).unwrap()
+		));
+	});
+
+	assert!(foreign::from_path::<_, Shape, _>(
+		String::new(),
+		String::new(),
+		(0..=MAX_DEPTH).map(|_| (Side::Left, String::new()))
+	)
+	.is_none());
+}
+
+#[test]
+fn from_sorted_pair_path_should_work() {
+	let smt = SparseMerkleTree::<_, SortedPair<CheckMergeOrder>>::new([7, 3, 5, 1, 4]);
+
+	(0..5).for_each(|i| {
+		let leaf = smt.proof_of([i]).leaves_with_index[0].1;
+		let path = smt.proof_of([i]).proof;
+		let proof =
+			foreign::from_sorted_pair_path::<_, CheckMergeOrder>(smt.root(), leaf, path).unwrap();
+
+		assert!(SparseMerkleTree::verify(proof));
+	});
+
+	let proof = foreign::from_sorted_pair_path::<_, CheckMergeOrder>(
+		smt.root(),
+		2,
+		smt.proof_of([0]).proof,
+	)
+	.unwrap();
+
+	assert!(!SparseMerkleTree::verify(proof));
+}
@@ -0,0 +1,117 @@
+// --- sparse-merkle-tree ---
+use crate::{forest::Forest, hash::test::*, *};
+
+#[test]
+fn default_nodes_should_match_the_merged_tree() {
+	let default_nodes = DefaultNodes::<_, CheckMergeOrder>::new(4);
+
+	(0..=9).for_each(|n| {
+		let leaves = || (1..=n).map(|i| i * 3);
+		let merged = SparseMerkleTree::<_, CheckMergeOrder>::new(leaves());
+		let shared = SparseMerkleTree::new_with_default_nodes(leaves(), &default_nodes);
+
+		assert_eq!(merged.nodes, shared.nodes);
+	});
+}
+
+#[test]
+fn forest_should_work() {
+	let mut forest = Forest::<&str, u32, DebugView>::new(8);
+
+	assert!(forest.insert("a", 1..=5).is_none());
+	assert!(forest.insert("c", 1..=4).is_none());
+	assert!(forest.insert("b", 1..=2).is_none());
+
+	assert_eq!(forest.len(), 3);
+	assert_eq!(forest.root_of(&"a"), Some(15));
+	assert_eq!(forest.root_of(&"b"), Some(3));
+	assert_eq!(forest.root_of(&"d"), None);
+	assert_eq!(forest.root(), 15 + 3 + 10);
+
+	let mut proof = forest.proof_of(&"a", [4, 0]).unwrap();
+
+	proof.sort();
+
+	assert!(SparseMerkleTree::verify(proof));
+	assert!(forest.proof_of(&"d", [0]).is_none());
+
+	// The roots tree in the key order: `[15, 3, 10, 0]`.
+	assert_eq!(forest.proof_of_tree(&"b").unwrap().proof, [15, 10]);
+	assert!(SparseMerkleTree::verify(
+		forest.proof_of_tree(&"c").unwrap()
+	));
+
+	assert_eq!(forest.remove(&"c").map(|tree| tree.root()), Some(10));
+	assert_eq!(forest.root(), 15 + 3);
+}
+
+#[test]
+fn aggregated_proof_should_work() {
+	let mut forest = Forest::<u32, u32, CheckMergeOrder>::new(8);
+
+	(0..5).for_each(|epoch| {
+		forest.insert(epoch, (1..=epoch + 3).map(|i| i * epoch));
+	});
+
+	let proof = forest.aggregated_proof_of(&3, [4, 1]).unwrap();
+
+	assert_eq!(proof.root(), &forest.root());
+	assert_eq!(proof.tree_index(), Some(3));
+	assert!(SparseMerkleTree::verify_aggregated(proof.clone()));
+	assert!(forest.aggregated_proof_of(&5, [0]).is_none());
+
+	// The leaves proof of another tree.
+	let mut mixed = proof.clone();
+
+	mixed.leaves_proof = forest.proof_of(&2, [1]).unwrap();
+
+	assert!(!SparseMerkleTree::verify_aggregated(mixed));
+
+	// Both proofs are valid, but not chained.
+	let mut unchained = proof.clone();
+
+	unchained.tree_proof = forest.proof_of_tree(&2).unwrap();
+
+	assert!(SparseMerkleTree::verify(unchained.tree_proof.clone()));
+	assert!(!SparseMerkleTree::verify_aggregated(unchained));
+
+	let mut tampered = proof;
+
+	tampered.leaves_proof.leaves_with_index[0].1 += 1;
+
+	assert!(!SparseMerkleTree::verify_aggregated(tampered));
+}
+
+#[test]
+fn new_with_depth_should_work() {
+	let default_nodes = DefaultNodes::<_, CheckMergeOrder>::new(4);
+
+	(0..=9).for_each(|n| {
+		let leaves = || 1..=n;
+		let padded = (1..=16).map(|i| if i <= n { i } else { 0 });
+		let smt = SparseMerkleTree::new_with_depth(leaves(), 4, &default_nodes).unwrap();
+
+		assert_eq!(
+			smt.nodes,
+			SparseMerkleTree::<_, CheckMergeOrder>::new(padded).nodes
+		);
+		assert_eq!(smt.depth(), 4);
+		assert_eq!(smt.non_empty_leaves_count(), n);
+
+		if n != 0 {
+			let proof = smt.proof_of([n - 1]);
+
+			assert_eq!(proof.proof.len(), 4);
+			assert!(SparseMerkleTree::verify(proof));
+		}
+	});
+
+	assert!(matches!(
+		SparseMerkleTree::new_with_depth(1..=17, 4, &default_nodes),
+		Err(TreeError::TooManyLeaves)
+	));
+	assert!(matches!(
+		SparseMerkleTree::new_with_depth(1..=1, 31, &default_nodes),
+		Err(TreeError::InvalidDepth)
+	));
+}
@@ -0,0 +1,40 @@
+// --- sparse-merkle-tree ---
+use crate::{hash::test::*, incremental::IncrementalRoot, *};
+
+#[test]
+fn incremental_root_should_match_the_tree() {
+	let mut incremental = IncrementalRoot::<_, CheckMergeOrder>::new();
+
+	assert_eq!(
+		incremental.root(),
+		SparseMerkleTree::<_, CheckMergeOrder>::empty().root()
+	);
+
+	(1..=33).for_each(|n| {
+		assert_eq!(incremental.push(n), n - 1);
+		assert_eq!(incremental.non_empty_leaves_count(), n);
+		assert_eq!(
+			incremental.root(),
+			SparseMerkleTree::<_, CheckMergeOrder>::new(1..=n).root()
+		);
+	});
+
+	// Only one node per height.
+	assert_eq!(incremental.frontier().len(), 6);
+
+	let mut restored =
+		IncrementalRoot::<_, CheckMergeOrder>::from_frontier(incremental.frontier().to_vec(), 33)
+			.unwrap();
+
+	assert_eq!(restored.root(), incremental.root());
+	assert_eq!(restored.push(34), 33);
+	assert_eq!(
+		restored.root(),
+		SparseMerkleTree::<_, CheckMergeOrder>::new(1..=34).root()
+	);
+	assert!(IncrementalRoot::<_, CheckMergeOrder>::from_frontier(
+		incremental.frontier().to_vec(),
+		31
+	)
+	.is_none());
+}
@@ -0,0 +1,104 @@
+// --- sparse-merkle-tree ---
+use crate::{
+	hash::test::*,
+	instrument::{Instrument, MergeBudget, MergeCounter, Operation},
+	tests::TestSparseMerkleTrie,
+	*,
+};
+
+#[test]
+fn merge_counter_should_work() {
+	let mut counter = MergeCounter::default();
+	//                15
+	//        0               15
+	//    0       0       10      5
+	//  0   0   0   0   3   7   5   0
+	// 0 0 0 0 0 0 0 0 1 2 3 4 5 0 0 0
+	let smt = TestSparseMerkleTrie::<DebugView>::new_with_instrument(1..=5, &mut counter);
+
+	assert_eq!(counter, MergeCounter { new: 7, verify: 0 });
+
+	[([0].as_ref(), 3), (&[4, 0], 5), (&[3, 2, 1, 0], 4)]
+		.iter()
+		.for_each(|(indices, merges)| {
+			let mut counter = MergeCounter::default();
+
+			assert!(TestSparseMerkleTrie::verify_with_instrument(
+				smt.proof_of(*indices),
+				&mut counter
+			));
+			assert_eq!(counter.verify, *merges);
+		});
+}
+
+#[test]
+fn hooks_should_be_paired() {
+	#[derive(Default)]
+	struct Recorder(Vec<(bool, Operation)>);
+	impl Instrument for Recorder {
+		fn enter(&mut self, operation: Operation) {
+			self.0.push((true, operation));
+		}
+
+		fn exit(&mut self, operation: Operation) {
+			self.0.push((false, operation));
+		}
+	}
+
+	let mut recorder = Recorder::default();
+	let smt = TestSparseMerkleTrie::<DebugView>::new_with_instrument(1..=4, &mut recorder);
+	let proof = smt.proof_of_with_instrument([1], &mut recorder);
+
+	TestSparseMerkleTrie::verify_with_instrument(proof, &mut recorder);
+
+	assert_eq!(
+		recorder.0,
+		[
+			(true, Operation::New),
+			(false, Operation::New),
+			(true, Operation::ProofOf),
+			(false, Operation::ProofOf),
+			(true, Operation::Verify),
+			(false, Operation::Verify),
+		]
+	);
+}
+
+#[test]
+fn verify_with_budget_should_work() {
+	let smt = TestSparseMerkleTrie::<DebugView>::new(1..=5);
+
+	[([0].as_ref(), 3), (&[4, 0], 5), (&[3, 2, 1, 0], 4)]
+		.iter()
+		.for_each(|(indices, merges)| {
+			let proof = smt.proof_of(*indices);
+
+			assert_eq!(
+				TestSparseMerkleTrie::verify_with_budget(proof.clone(), *merges),
+				Ok(true)
+			);
+			assert_eq!(
+				TestSparseMerkleTrie::verify_with_budget(proof, merges - 1),
+				Err(BudgetExceeded)
+			);
+		});
+
+	// Within the budget, but invalid.
+	let mut proof = smt.proof_of([0]);
+
+	proof.root = 0;
+
+	assert_eq!(
+		TestSparseMerkleTrie::verify_with_budget(proof, 3),
+		Ok(false)
+	);
+
+	let mut budget = MergeBudget::new(2);
+
+	assert!(!TestSparseMerkleTrie::verify_with_instrument(
+		smt.proof_of([0]),
+		&mut budget
+	));
+	assert!(budget.exceeded());
+	assert_eq!(budget.remaining(), 0);
+}
@@ -0,0 +1,89 @@
+// --- sparse-merkle-tree ---
+use crate::{hash::test::*, kary::*, *};
+
+/// `3 * (3 * (3 * a + b) + c) + d`, so the order matters.
+struct CheckMergeOrder4;
+impl MergeN for CheckMergeOrder4 {
+	type Item = u32;
+
+	fn empty() -> Self::Item {
+		0
+	}
+
+	fn merge_n(children: &[Self::Item]) -> Self::Item {
+		children
+			.iter()
+			.fold(0, |acc, child| acc.wrapping_mul(3).wrapping_add(*child))
+	}
+}
+
+#[test]
+fn binary_should_match_the_sparse_merkle_tree() {
+	for n in 0..=9 {
+		let kary = KaryTree::<_, Binary<CheckMergeOrder>, 2>::new(1..=n);
+		let smt = SparseMerkleTree::<_, CheckMergeOrder>::new(1..=n);
+
+		assert_eq!(kary.root(), smt.root());
+		assert_eq!(kary.depth(), smt.depth());
+	}
+}
+
+#[test]
+fn kary_should_work() {
+	for n in 1..=40 {
+		let tree = KaryTree::<_, CheckMergeOrder4, 4>::new(1..=n);
+
+		assert!(4_u32.pow(tree.depth()) >= n);
+
+		for indices in [
+			(0..n).collect::<Vec<_>>(),
+			(0..n).rev().step_by(3).collect(),
+			[n - 1, 0].to_vec(),
+		] {
+			let mut indices = indices;
+
+			indices.dedup();
+
+			let proof = tree.proof_of(&indices).unwrap();
+
+			assert!(KaryTree::verify(&proof));
+
+			let mut tampered = proof.clone();
+
+			tampered.leaves_with_index[0].1 += 1;
+
+			assert!(!KaryTree::verify(&tampered));
+
+			let mut tampered = proof.clone();
+
+			tampered.siblings.push(0);
+
+			assert!(!KaryTree::verify(&tampered));
+		}
+
+		assert!(tree.proof_of([n]).is_none());
+		assert!(tree.proof_of([0, 0]).is_none());
+	}
+
+	let tree = KaryTree::<_, CheckMergeOrder4, 4>::new(1..=5);
+	let m = CheckMergeOrder4::merge_n;
+
+	assert_eq!(tree.depth(), 2);
+	assert_eq!(tree.root(), m(&[m(&[1, 2, 3, 4]), m(&[5, 0, 0, 0]), 0, 0]));
+	// A single leaf proof takes `(ARITY - 1) * depth` siblings.
+	assert_eq!(tree.proof_of([4]).unwrap().siblings.len(), 6);
+}
+
+#[test]
+fn indices_out_of_u32_should_be_rejected() {
+	let mut proof = KaryTree::<_, CheckMergeOrder4, 4>::new(1..=5)
+		.proof_of([0])
+		.unwrap();
+
+	// 4^16 leaves, the group of the last one ends at `2^32`.
+	proof.depth = 16;
+	proof.leaves_with_index = [(u32::MAX, 1)].into();
+	proof.siblings = [0; 3 * 16].into();
+
+	assert!(!KaryTree::verify(&proof));
+}
@@ -1,5 +1,3 @@
-// --- core ---
-use core::fmt::{Debug, Formatter, Result};
 // --- sparse-merkle-tree ---
 use crate::{
 	hash::{Hasher, Keccak256},
@@ -8,23 +6,8 @@ use crate::{
 
 #[test]
 fn keccak_should_work() {
-	impl Debug for SparseMerkleTree<[u8; 32]> {
-		fn fmt(&self, f: &mut Formatter) -> Result {
-			f.debug_struct("SparseMerkleTree")
-				.field(
-					"nodes",
-					&self
-						.nodes
-						.iter()
-						.map(|node| array_bytes::bytes2hex("0x", node))
-						.collect::<Vec<_>>(),
-				)
-				.finish()
-		}
-	}
-
 	let _ = pretty_env_logger::try_init();
-	let smt = SparseMerkleTree::new::<_, Keccak256>(
+	let smt = SparseMerkleTree::<_, Keccak256>::new(
 		// Secret phrase:       ladder wisdom tenant unique illegal soccer side tobacco flag average help explain
 		//   Secret seed:       0xeccfc6d7efea70eec2f6d19acdbb20012732a32268705037bd99c9d27a1bf6e6
 		//   Public key (hex):  0x02d3879027ad442b2b56034aab3bb078a09821029e3ac44d9807c1dcd447772ef1
@@ -62,8 +45,15 @@ fn keccak_should_work() {
 		.map(|hex_pub_key| Keccak256::hash(array_bytes::hex2bytes_unchecked(hex_pub_key))),
 	);
 
+	// The same output as the former test-local `Debug` impl, the nodes in hex.
 	#[cfg(feature = "debug")]
-	log::debug!("{:?}", smt);
+	log::debug!(
+		"SparseMerkleTree {{ nodes: {:?} }}",
+		smt.as_nodes()
+			.iter()
+			.map(|node| array_bytes::bytes2hex("0x", node))
+			.collect::<Vec<_>>()
+	);
 
 	[
 		[0].as_ref(),
@@ -80,10 +70,58 @@ fn keccak_should_work() {
 	]
 	.iter()
 	.for_each(|indices| {
-		let mut proof = smt.proof_of(&indices);
+		let mut proof = smt.proof_of(*indices);
 
 		proof.sort();
 
-		assert!(SparseMerkleTree::verify::<Keccak256>(proof));
+		assert!(SparseMerkleTree::verify(proof));
+	});
+}
+
+#[test]
+fn new_from_data_should_work() {
+	let payloads = (0..5_u8).map(|i| [i; 1_000].to_vec()).collect::<Vec<_>>();
+	let smt = SparseMerkleTree::<_, Keccak256>::new_from_data(
+		payloads.iter().map(|payload| payload.chunks(64)),
+	);
+
+	assert_eq!(
+		smt.nodes,
+		SparseMerkleTree::<_, Keccak256>::new(payloads.iter().map(Keccak256::hash)).nodes
+	);
+}
+
+#[test]
+fn new_keyed_should_work() {
+	let accounts = ["alice", "bob", "charlie", "dave", "eve"];
+	let leaf = |account: &str| Keccak256::hash(account.as_bytes().repeat(2));
+	let entries = || accounts.iter().map(|account| (*account, leaf(account)));
+	let (smt, key_indices) = SparseMerkleTree::<_, Keccak256>::new_keyed(entries());
+
+	assert_eq!(key_indices.len(), accounts.len());
+
+	// Independent of the input order and the duplicates.
+	let (shuffled, shuffled_key_indices) = SparseMerkleTree::<_, Keccak256>::new_keyed(
+		entries().rev().chain([("bob", Keccak256::hash("ignored"))]),
+	);
+
+	assert_eq!(smt.as_nodes(), shuffled.as_nodes());
+	assert_eq!(key_indices, shuffled_key_indices);
+
+	// Any `IntoIterator`.
+	let (collected, _) = SparseMerkleTree::<_, Keccak256>::new_keyed(entries().collect::<Vec<_>>());
+
+	assert_eq!(smt.as_nodes(), collected.as_nodes());
+
+	let mut hashes = accounts.map(Keccak256::hash);
+
+	hashes.sort();
+
+	accounts.iter().for_each(|account| {
+		let i = key_indices[account];
+
+		assert_eq!(hashes[i as usize], Keccak256::hash(account));
+		assert!(SparseMerkleTree::verify(smt.proof_of([i])));
+		assert_eq!(smt.proof_of([i]).leaves_with_index[0].1, leaf(account));
 	});
 }
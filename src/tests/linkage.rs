@@ -0,0 +1,67 @@
+// --- sparse-merkle-tree ---
+use crate::{hash::test::*, linkage::Linkage, *};
+
+/// Mix the bits, so the samples spread over the leaves.
+struct Mix;
+impl Merge for Mix {
+	type Item = [u8; 4];
+
+	fn empty() -> Self::Item {
+		[0; 4]
+	}
+
+	fn merge(l: &Self::Item, r: &Self::Item) -> Self::Item {
+		let l = u32::from_be_bytes(*l).wrapping_mul(0x9e37_79b1);
+
+		(l ^ u32::from_be_bytes(*r)).rotate_left(5).to_be_bytes()
+	}
+}
+
+#[test]
+fn linkage_should_work() {
+	let leaves = || (1..=20_u32).map(u32::to_be_bytes);
+	let a = SparseMerkleTree::<_, Mix>::new(leaves());
+	let b = SparseMerkleTree::<_, DebugBytes>::new(leaves());
+	let linkage = Linkage::new(&a, &b, 5).unwrap();
+
+	assert_eq!(linkage.a.leaves_with_index.len(), 5);
+	assert!(linkage.verify(20, 5));
+	// The auditor asks for more samples.
+	assert!(!linkage.verify(20, 6));
+	// The auditor expects another leaves count, whose challenge differs or whose depth mismatches.
+	[1, 19, 21, 33, 1 << MAX_DEPTH, u32::MAX]
+		.iter()
+		.for_each(|leaves_count| assert!(!linkage.verify(*leaves_count, 5)));
+
+	// All the leaves.
+	let complete = Linkage::new(&a, &b, 20).unwrap();
+
+	assert_eq!(complete.a.leaves_with_index.len(), 20);
+	assert!(complete.verify(20, 20));
+	assert!(complete.verify(20, 100));
+	// Only a prefix of the leaves.
+	assert!(!complete.verify(21, 21));
+
+	// A different leaf.
+	let c = SparseMerkleTree::<_, DebugBytes>::new(leaves().map(|leaf| {
+		if leaf == linkage.a.leaves_with_index[0].1 {
+			[0xff; 4]
+		} else {
+			leaf
+		}
+	}));
+	let indices = linkage
+		.a
+		.leaves_with_index
+		.iter()
+		.map(|(i, _)| i - a.capacity())
+		.collect::<Vec<_>>();
+	let forged = Linkage {
+		a: a.proof_of(&indices),
+		b: c.proof_of(&indices),
+	};
+
+	assert!(Linkage::new(&a, &c, 5).is_none());
+	assert!(!forged.verify(20, 5));
+	assert!(Linkage::new(&SparseMerkleTree::<_, Mix>::empty(), &b, 5).is_none());
+}
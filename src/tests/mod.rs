@@ -1,30 +1,50 @@
+mod audit;
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+mod codec;
+mod commitment;
+mod compact_tree;
+mod compressed;
+mod const_root;
+mod dyn_merge;
+mod foreign;
+mod forest;
+mod incremental;
+mod instrument;
+mod kary;
 #[cfg(feature = "keccak")]
 mod keccak;
+mod linkage;
+mod proof_cache;
+mod shared;
+#[cfg(feature = "solidity-codegen")]
+mod solidity;
+mod stats;
+#[cfg(feature = "proptest")]
+mod strategies;
+#[cfg(feature = "test-utils")]
+mod test_utils;
+#[cfg(feature = "test-vector")]
+mod test_vector;
+mod virtual_tree;
 
 // --- core ---
 use core::fmt::{Debug, Formatter, Result};
 // --- sparse-merkle-tree ---
 use crate::{hash::test::*, *};
 
-type TestSparseMerkleTrie = SparseMerkleTree<u32>;
+type TestSparseMerkleTrie<M = DebugView> = SparseMerkleTree<u32, M>;
 
-impl TestSparseMerkleTrie {
-	fn new_with_leaves_count<T>(half_leaves_count: u32) -> Self
-	where
-		T: Merge<Item = u32>,
-	{
+impl<M> TestSparseMerkleTrie<M>
+where
+	M: Merge<Item = u32>,
+{
+	fn new_with_leaves_count(half_leaves_count: u32) -> Self {
 		let mut leaves = Vec::new();
 
 		(1..=half_leaves_count).for_each(|i| leaves.push(i));
 
-		TestSparseMerkleTrie::new::<_, T>(leaves.into_iter())
-	}
-}
-impl Debug for TestSparseMerkleTrie {
-	fn fmt(&self, f: &mut Formatter) -> Result {
-		f.debug_struct("TestSparseMerkleTrie")
-			.field("nodes", &self.nodes)
-			.finish()
+		TestSparseMerkleTrie::new(leaves)
 	}
 }
 
@@ -56,7 +76,7 @@ impl Debug for TestProof {
 			.field(
 				"leaves",
 				&format_args!("{:?}", {
-					let half_leaves_count = self.smt.half_leaves_count();
+					let half_leaves_count = self.smt.capacity();
 
 					self.indices
 						.iter()
@@ -64,7 +84,7 @@ impl Debug for TestProof {
 						.collect::<Vec<_>>()
 				}),
 			)
-			.field("proof", &format_args!("{:?}", &self.proof))
+			.field("proof", &format_args!("{:?}", self.proof))
 			.finish()
 	}
 }
@@ -77,11 +97,12 @@ fn smt_should_work() {
 	//    0       0       10      5
 	//  0   0   0   0   3   7   5   0
 	// 0 0 0 0 0 0 0 0 1 2 3 4 5 0 0 0
-	let smt = TestSparseMerkleTrie::new_with_leaves_count::<DebugView>(5);
+	let smt = TestSparseMerkleTrie::<DebugView>::new_with_leaves_count(5);
 
 	#[cfg(feature = "debug")]
 	log::debug!("{:?}", smt);
 
+	assert_eq!((smt.nodes_count(), smt.capacity(), smt.depth()), (16, 8, 3));
 	assert_eq!(smt.nodes, {
 		let mut nodes = Vec::new();
 
@@ -102,7 +123,7 @@ fn proof_should_work() {
 	// leaves  0 0 0 0 1 2 3 4
 	// indices         0 1 2 3
 	let mut debug_proof =
-		TestProof::of_smt(TestSparseMerkleTrie::new_with_leaves_count::<DebugView>(4));
+		TestProof::of_smt(TestSparseMerkleTrie::<DebugView>::new_with_leaves_count(4));
 
 	[
 		([0].as_ref(), [2, 7].as_ref()),
@@ -134,7 +155,7 @@ fn proof_should_work() {
 	// leaves  0 0 0 0 0 0 0 0 1 2 3 4 5 0 0 0
 	// indices                 0 1 2 3 4 5 6 7
 	let mut debug_proof =
-		TestProof::of_smt(TestSparseMerkleTrie::new_with_leaves_count::<DebugView>(5));
+		TestProof::of_smt(TestSparseMerkleTrie::<DebugView>::new_with_leaves_count(5));
 
 	[
 		([0].as_ref(), [2, 7, 5].as_ref()),
@@ -210,28 +231,1048 @@ fn verify_should_work() {
 	// leaves       0 0 0 0 0 0 0 0 1 2 3 4 5 0 0 0
 	// indices                      0 1 2 3 4 5 6 7
 	// node indices 0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
-	let smt = TestSparseMerkleTrie::new_with_leaves_count::<DebugView>(5);
+	let smt = TestSparseMerkleTrie::<DebugView>::new_with_leaves_count(5);
 
 	indices_set.iter().for_each(|indices| {
-		let mut proof = smt.proof_of(indices);
+		let mut proof = smt.proof_of(*indices);
 		let mut indices = indices.to_vec();
 
 		proof.sort();
 		indices.sort_by(|a, b| b.cmp(a));
 
-		assert!(TestSparseMerkleTrie::verify::<DebugView>(proof));
-		assert!(TestSparseMerkleTrie::verify::<DebugView>(
-			smt.proof_of(&indices)
-		));
+		assert!(TestSparseMerkleTrie::verify(proof));
+		assert!(TestSparseMerkleTrie::verify(smt.proof_of(&indices)));
 	});
 
-	let smt = TestSparseMerkleTrie::new_with_leaves_count::<CheckMergeOrder>(5);
+	let smt = TestSparseMerkleTrie::<CheckMergeOrder>::new_with_leaves_count(5);
 
 	indices_set.iter().for_each(|indices| {
-		let mut proof = smt.proof_of(indices);
+		let mut proof = smt.proof_of(*indices);
 
 		proof.sort();
 
-		assert!(TestSparseMerkleTrie::verify::<CheckMergeOrder>(proof));
+		assert!(TestSparseMerkleTrie::verify(proof));
+	});
+}
+
+#[test]
+fn first_divergence_should_work() {
+	let smt = TestSparseMerkleTrie::<DebugView>::new_with_leaves_count(5);
+
+	assert_eq!(smt.first_divergence(&smt), None);
+	assert_eq!(
+		smt.first_divergence(&TestSparseMerkleTrie::<DebugView>::new_with_leaves_count(4)),
+		Some((0, 0))
+	);
+
+	//                15
+	//        0               15
+	//    0       0       10      5
+	//  0   0   0   0   3   7   5   0
+	// 0 0 0 0 0 0 0 0 1 2 3 4 5 0 0 0
+	[
+		([0, 2, 3, 4, 5].as_ref(), (3, 0)),
+		(&[1, 2, 3, 0, 5], (3, 3)),
+		(&[1, 2, 3, 4, 0], (3, 4)),
+		(&[1, 2, 3, 4, 5, 1], (3, 5)),
+	]
+	.iter()
+	.for_each(|(leaves, divergence)| {
+		let other = TestSparseMerkleTrie::<DebugView>::new(leaves.iter().cloned());
+
+		assert_eq!(smt.first_divergence(&other), Some(*divergence));
+	});
+
+	// The sums collide, only the leaves differ.
+	assert_eq!(
+		smt.first_divergence(&TestSparseMerkleTrie::<DebugView>::new(
+			[2, 1, 3, 4, 5].iter().cloned()
+		)),
+		None
+	);
+}
+
+#[test]
+fn non_default_empty_should_work() {
+	// No `Default` here.
+	#[derive(Clone, Debug, PartialEq)]
+	struct Node(u32);
+	struct OneEmpty;
+	impl Merge for OneEmpty {
+		type Item = Node;
+
+		fn empty() -> Self::Item {
+			Node(1)
+		}
+
+		fn merge(l: &Self::Item, r: &Self::Item) -> Self::Item {
+			Node(l.0 + r.0)
+		}
+	}
+
+	//          7
+	//      3       4
+	//    1   2   3   1
+	let smt = SparseMerkleTree::<_, OneEmpty>::new((1..=3).map(Node));
+
+	assert_eq!(smt.root(), Node(7));
+	assert_eq!(smt.proof_of([2]).proof, [Node(1), Node(3)]);
+	assert!(SparseMerkleTree::verify(smt.proof_of([2])));
+	assert_eq!(DefaultNodes::<_, OneEmpty>::new(2).get(2), Some(&Node(4)));
+}
+
+#[test]
+fn committed_root_should_work() {
+	let smt = TestSparseMerkleTrie::<DebugView>::new_with_leaves_count(5);
+	// Pad with an explicit empty leaf.
+	let padded_smt = TestSparseMerkleTrie::<DebugView>::new([1, 2, 3, 4, 5, 0].iter().cloned());
+
+	assert_eq!(smt.root(), padded_smt.root());
+	assert_eq!(smt.committed_root(), 15 + 5);
+	assert_eq!(padded_smt.committed_root(), 15 + 6);
+
+	let proof = smt.committed_proof_of([4, 0]);
+
+	assert_eq!(proof.non_empty_leaves_count, Some(5));
+	assert!(TestSparseMerkleTrie::verify_committed(proof.clone()));
+	assert!(!TestSparseMerkleTrie::verify(proof.clone()));
+	assert!(!TestSparseMerkleTrie::verify_committed(
+		smt.proof_of([4, 0])
+	));
+
+	let mut forged = proof;
+
+	// The same root, but the leaf `4` isn't in a tree of size `4`.
+	forged.non_empty_leaves_count = Some(4);
+	forged.root = 15 + 4;
+
+	assert!(!TestSparseMerkleTrie::verify_committed(forged.clone()));
+
+	// Deeper than `MAX_DEPTH`, and `u32::MAX` is out of `next_power_of_two`.
+	[(1 << MAX_DEPTH) + 1, u32::MAX].into_iter().for_each(|n| {
+		forged.non_empty_leaves_count = Some(n);
+
+		assert!(!TestSparseMerkleTrie::verify_committed(forged.clone()));
 	});
+	assert!(TestSparseMerkleTrie::verify_committed(
+		padded_smt.committed_proof_of([5])
+	));
+}
+
+#[test]
+fn duplicate_last_padding_should_work() {
+	// Bitcoin style, the padding node is the last node on its level.
+	//                            1+2+3+4+5+6+5+6
+	//             1+2+3+4                         5+6+5+6
+	//     1+2             3+4             5+6             5+6
+	//  1       2       3       4       5       6       6       6
+	let smt =
+		TestSparseMerkleTrie::<CheckMergeOrder>::new_with_padding(1..=6, Padding::DuplicateLast);
+	let m = |l, r| CheckMergeOrder::merge(&l, &r);
+	let (l12, l34, l56) = (m(1, 2), m(3, 4), m(5, 6));
+
+	assert_eq!(smt.nodes[8..], [1, 2, 3, 4, 5, 6, 6, 6]);
+	assert_eq!(smt.nodes[4..8], [l12, l34, l56, l56]);
+	assert_eq!(smt.root(), m(m(l12, l34), m(l56, l56)));
+
+	[[5].as_ref(), &[5, 4], &[5, 3, 0]]
+		.iter()
+		.for_each(|indices| {
+			assert!(TestSparseMerkleTrie::verify(smt.proof_of(*indices)));
+		});
+
+	assert!(
+		TestSparseMerkleTrie::<DebugView>::new_with_padding(0..0, Padding::DuplicateLast)
+			.is_empty()
+	);
+}
+
+#[test]
+fn verify_against_roots_should_work() {
+	let smt = TestSparseMerkleTrie::<DebugView>::new_with_leaves_count(5);
+	let proof = smt.proof_of([4, 0]);
+
+	assert_eq!(
+		TestSparseMerkleTrie::verify_against_roots(&[10, 15, 15], &proof),
+		Some(1)
+	);
+	assert_eq!(
+		TestSparseMerkleTrie::verify_against_roots(&[10, 21], &proof),
+		None
+	);
+	assert_eq!(
+		TestSparseMerkleTrie::verify_against_roots(&[], &proof),
+		None
+	);
+	assert_eq!(
+		TestSparseMerkleTrie::verify_against_roots(&[0, 15], &smt.proof_of([5])),
+		None
+	);
+}
+
+#[test]
+fn merge_into_should_be_preferred() {
+	// --- core ---
+	use core::sync::atomic::{AtomicU32, Ordering};
+
+	static MERGES: AtomicU32 = AtomicU32::new(0);
+	static MERGES_INTO: AtomicU32 = AtomicU32::new(0);
+
+	struct InPlace;
+	impl Merge for InPlace {
+		type Item = u32;
+
+		fn empty() -> Self::Item {
+			0
+		}
+
+		fn merge(l: &Self::Item, r: &Self::Item) -> Self::Item {
+			MERGES.fetch_add(1, Ordering::Relaxed);
+
+			*l + *r
+		}
+
+		fn merge_into(out: &mut Self::Item, l: &Self::Item, r: &Self::Item) {
+			MERGES_INTO.fetch_add(1, Ordering::Relaxed);
+
+			*out = *l + *r;
+		}
+	}
+
+	let smt = TestSparseMerkleTrie::<InPlace>::new_with_leaves_count(5);
+
+	assert_eq!(smt.root(), 15);
+	assert_eq!(
+		(
+			MERGES.load(Ordering::Relaxed),
+			MERGES_INTO.load(Ordering::Relaxed)
+		),
+		(0, 7)
+	);
+
+	assert!(TestSparseMerkleTrie::verify(smt.proof_of([4, 0])));
+	// Only the first merge creates a fresh node.
+	assert_eq!(
+		(
+			MERGES.load(Ordering::Relaxed),
+			MERGES_INTO.load(Ordering::Relaxed)
+		),
+		(1, 11)
+	);
+}
+
+#[test]
+fn validate_structure_should_work() {
+	let smt = TestSparseMerkleTrie::<DebugView>::new_with_leaves_count(5);
+
+	[[0].as_ref(), &[4, 0], &[3, 2, 1, 0], &[4, 3, 2, 1, 0]]
+		.iter()
+		.for_each(|indices| {
+			let mut indices = indices.to_vec();
+
+			indices.sort_by(|a, b| b.cmp(a));
+
+			assert_eq!(smt.proof_of(&indices).validate_structure(), Ok(()));
+			assert_eq!(
+				smt.committed_proof_of(&indices).validate_structure(),
+				Ok(())
+			);
+		});
+
+	assert_eq!(
+		smt.proof_of([0, 4]).validate_structure(),
+		Err(ProofError::NotDescending)
+	);
+	assert_eq!(
+		smt.proof_of([4, 4]).validate_structure(),
+		Err(ProofError::DuplicateLeaf)
+	);
+	assert_eq!(
+		Proof::<u32, DebugView>::default().validate_structure(),
+		Err(ProofError::NoLeaves)
+	);
+
+	let mut proof = smt.proof_of([4, 0]);
+
+	proof.leaves_with_index[1].0 = 4;
+
+	assert_eq!(proof.validate_structure(), Err(ProofError::InvalidIndex));
+
+	let mut proof = smt.committed_proof_of([4, 0]);
+
+	proof.non_empty_leaves_count = Some(4);
+
+	assert_eq!(proof.validate_structure(), Err(ProofError::InvalidIndex));
+
+	// Deeper than `MAX_DEPTH`, and `u32::MAX` is out of `next_power_of_two`.
+	[(1 << MAX_DEPTH) + 1, u32::MAX].into_iter().for_each(|n| {
+		proof.non_empty_leaves_count = Some(n);
+
+		assert_eq!(proof.validate_structure(), Err(ProofError::InvalidIndex));
+	});
+
+	let mut proof = smt.proof_of([4, 0]);
+
+	proof.proof.pop();
+
+	assert_eq!(
+		proof.validate_structure(),
+		Err(ProofError::SiblingsCountMismatch {
+			expected: 4,
+			actual: 3
+		})
+	);
+}
+
+#[test]
+fn from_nodes_should_work() {
+	let smt = TestSparseMerkleTrie::<CheckMergeOrder>::new_with_leaves_count(5);
+	let restored =
+		TestSparseMerkleTrie::<CheckMergeOrder>::from_nodes(smt.nodes.clone(), 5).unwrap();
+
+	assert_eq!(restored.nodes, smt.nodes);
+	assert_eq!(restored.root(), smt.root());
+
+	let padded =
+		TestSparseMerkleTrie::<CheckMergeOrder>::new_with_padding(1..=5, Padding::DuplicateLast);
+
+	assert!(TestSparseMerkleTrie::<CheckMergeOrder>::from_nodes(padded.nodes, 5).is_ok());
+
+	// The padding is checked too.
+	let plain = TestSparseMerkleTrie::<CheckMergeOrder>::new(1..=5);
+	let mut nodes = plain.nodes.clone();
+
+	nodes[15] = 1;
+
+	assert_eq!(
+		TestSparseMerkleTrie::<CheckMergeOrder>::from_nodes(nodes, 5).err(),
+		Some(TreeError::InconsistentNode(15))
+	);
+
+	let mut nodes = plain.nodes;
+
+	nodes[7] = 1;
+
+	assert_eq!(
+		TestSparseMerkleTrie::<CheckMergeOrder>::from_nodes(nodes, 5).err(),
+		Some(TreeError::InconsistentNode(7))
+	);
+
+	assert_eq!(
+		TestSparseMerkleTrie::<CheckMergeOrder>::from_nodes(smt.nodes.clone(), 4).err(),
+		Some(TreeError::InvalidNodesCount)
+	);
+	assert_eq!(
+		TestSparseMerkleTrie::<CheckMergeOrder>::from_nodes(smt.nodes[..8].to_vec(), 5).err(),
+		Some(TreeError::InvalidNodesCount)
+	);
+	// Deeper than `MAX_DEPTH`, and `u32::MAX` is out of `next_power_of_two`.
+	[(1 << MAX_DEPTH) + 1, u32::MAX].into_iter().for_each(|n| {
+		assert_eq!(
+			TestSparseMerkleTrie::<CheckMergeOrder>::from_nodes(Vec::new(), n).err(),
+			Some(TreeError::InvalidNodesCount)
+		);
+	});
+
+	let mut nodes = smt.nodes.clone();
+
+	nodes[10] += 1;
+
+	assert_eq!(
+		TestSparseMerkleTrie::<CheckMergeOrder>::from_nodes(nodes.clone(), 5).err(),
+		Some(TreeError::InconsistentNode(5))
+	);
+
+	// Trust whatever it is.
+	let unchecked = TestSparseMerkleTrie::<CheckMergeOrder>::from_nodes_unchecked(nodes, 5);
+
+	assert_eq!(unchecked.root(), smt.root());
+	assert_eq!(unchecked.proof_of([2]).leaves_with_index, [(10, 4)]);
+}
+
+#[test]
+fn prove_from_leaves_should_work() {
+	for n in 0..=9 {
+		let smt = TestSparseMerkleTrie::<CheckMergeOrder>::new(1..=n);
+
+		for indices in [
+			Vec::new(),
+			(0..n).rev().collect(),
+			(0..n).rev().step_by(2).collect(),
+			(0..n).rev().skip(1).step_by(3).collect(),
+			(0..n).collect(),
+		] {
+			let proof = TestSparseMerkleTrie::<CheckMergeOrder>::prove_from_leaves(1..=n, &indices);
+
+			assert!(proof == smt.proof_of(&indices));
+		}
+
+		assert!(
+			TestSparseMerkleTrie::<CheckMergeOrder>::prove_from_leaves(1..=n, &[n])
+				== Proof::default()
+		);
+	}
+
+	// The size hint of a `filter` is inexact, the leaves are counted instead.
+	let smt = TestSparseMerkleTrie::<CheckMergeOrder>::new(1..=5);
+
+	[[4, 0].as_ref(), &[2], &[]].iter().for_each(|indices| {
+		assert!(
+			TestSparseMerkleTrie::<CheckMergeOrder>::prove_from_leaves(
+				(1..=10).filter(|leaf| *leaf <= 5),
+				indices
+			) == smt.proof_of(*indices)
+		);
+	});
+	assert!(
+		TestSparseMerkleTrie::<CheckMergeOrder>::prove_from_leaves(
+			(1..=10).filter(|leaf| *leaf <= 5),
+			&[5]
+		) == Proof::default()
+	);
+}
+
+#[test]
+fn refresh_should_work() {
+	let leaves = (1..=9).collect::<Vec<u32>>();
+	let smt = TestSparseMerkleTrie::<CheckMergeOrder>::new(leaves.clone());
+	let proof = smt.proof_of([7, 3, 0]);
+	let updated = |updates: &[(u32, u32)]| {
+		let mut leaves = leaves.clone();
+
+		updates
+			.iter()
+			.for_each(|(i, leaf)| leaves[*i as usize] = *leaf);
+
+		TestSparseMerkleTrie::<CheckMergeOrder>::new(leaves).proof_of([7, 3, 0])
+	};
+
+	// Proven leaves.
+	assert!(proof.refresh(&[(3, 40), (0, 10)]) == Some(updated(&[(3, 40), (0, 10)])));
+	// Sibling leaves.
+	assert!(
+		proof.refresh(&[(2, 30), (6, 70), (1, 20)]) == Some(updated(&[(2, 30), (6, 70), (1, 20)]))
+	);
+	assert!(TestSparseMerkleTrie::<CheckMergeOrder>::verify(
+		proof.refresh(&[(2, 30)]).unwrap()
+	));
+	// Inside a sibling subtree.
+	assert!(proof.refresh(&[(8, 90)]).is_none());
+	assert!(proof.refresh(&[(5, 60)]).is_none());
+	// Out of the capacity.
+	assert!(proof.refresh(&[(16, 1)]).is_none());
+	assert!(proof.refresh(&[]) == Some(proof.clone()));
+}
+
+#[test]
+fn canonical_root_should_work() {
+	let default_nodes = DefaultNodes::<_, CheckMergeOrder>::new(5);
+
+	for n in 1..=17 {
+		let smt = TestSparseMerkleTrie::<CheckMergeOrder>::new(1..=n);
+		let canonical_root = smt.canonical_root();
+
+		assert_eq!(
+			TestSparseMerkleTrie::new_with_depth(1..=n, 5, &default_nodes)
+				.unwrap()
+				.canonical_root(),
+			canonical_root
+		);
+		assert_eq!(
+			TestSparseMerkleTrie::<CheckMergeOrder>::new_with_padding(
+				1..=n,
+				Padding::DuplicateLast
+			)
+			.canonical_root(),
+			canonical_root
+		);
+
+		if n.is_power_of_two() {
+			assert_eq!(canonical_root, smt.root());
+		}
+	}
+
+	let m = CheckMergeOrder::merge;
+
+	// The trailing empty leaf is ignored.
+	assert_eq!(
+		TestSparseMerkleTrie::<CheckMergeOrder>::new(1..=3).canonical_root(),
+		m(&m(&1, &2), &3)
+	);
+	assert_eq!(
+		TestSparseMerkleTrie::<CheckMergeOrder>::new(1..=5).canonical_root(),
+		m(&m(&m(&1, &2), &m(&3, &4)), &5)
+	);
+	assert_eq!(
+		TestSparseMerkleTrie::<CheckMergeOrder>::new(0..0).canonical_root(),
+		0
+	);
+}
+
+#[test]
+fn proof_should_be_ordered() {
+	let smt = TestSparseMerkleTrie::<DebugView>::new(1..=9);
+	let proofs = [
+		smt.proof_of([3]),
+		smt.proof_of([1]),
+		smt.proof_of([3]),
+		smt.committed_proof_of([3]),
+	];
+	let set = proofs
+		.iter()
+		.cloned()
+		.collect::<alloc::collections::BTreeSet<_>>();
+
+	assert_eq!(set.len(), 3);
+	assert!(proofs[0].cmp(&proofs[2]) == core::cmp::Ordering::Equal);
+	assert!(proofs[0].partial_cmp(&proofs[1]) == Some(proofs[0].cmp(&proofs[1])));
+	// Same root, differ in the leaves.
+	assert!(proofs[1] < proofs[0]);
+}
+
+#[test]
+fn empty_tree_should_work() {
+	let empty = TestSparseMerkleTrie::<CheckMergeOrder>::empty();
+
+	assert!(empty.is_empty());
+	assert!(empty.as_nodes().is_empty());
+	assert_eq!(empty.root(), CheckMergeOrder::empty());
+	assert_eq!(empty.canonical_root(), CheckMergeOrder::empty());
+	assert_eq!(empty.depth(), 0);
+	assert_eq!(empty.capacity(), 0);
+	assert_eq!(empty.non_empty_leaves_count(), 0);
+	assert_eq!(
+		empty.first_divergence(&TestSparseMerkleTrie::new(0..0)),
+		None
+	);
+	assert_eq!(empty.finalize().root(), CheckMergeOrder::empty());
+
+	let empty = TestSparseMerkleTrie::<CheckMergeOrder>::new(0..0);
+
+	assert!(empty.is_empty());
+	assert!(!TestSparseMerkleTrie::<CheckMergeOrder>::new(0..1).is_empty());
+	assert!(
+		TestSparseMerkleTrie::<CheckMergeOrder>::from_nodes(Vec::new(), 0)
+			.unwrap()
+			.is_empty()
+	);
+	assert!(TestSparseMerkleTrie::<CheckMergeOrder>::from_nodes(Vec::new(), 1).is_err());
+
+	// Nothing to prove.
+	[[].as_ref(), &[0]].iter().for_each(|indices| {
+		let proof = empty.proof_of(*indices);
+
+		assert!(proof.leaves_with_index.is_empty());
+		assert_eq!(proof.root, CheckMergeOrder::empty());
+		assert!(!TestSparseMerkleTrie::verify(proof));
+	});
+}
+
+#[cfg(not(feature = "debug"))]
+#[test]
+fn hash_without_debug_should_work() {
+	#[derive(Clone, PartialEq)]
+	struct Opaque(u32);
+	struct OpaqueMerge;
+	impl Merge for OpaqueMerge {
+		type Item = Opaque;
+
+		fn empty() -> Self::Item {
+			Opaque(0)
+		}
+
+		fn merge(l: &Self::Item, r: &Self::Item) -> Self::Item {
+			Opaque(l.0 + r.0)
+		}
+	}
+
+	let smt = SparseMerkleTree::<_, OpaqueMerge>::new((1..=9).map(Opaque));
+
+	assert!(smt.root() == Opaque(45));
+	assert!(SparseMerkleTree::verify(smt.proof_of([8, 0])));
+}
+
+#[test]
+fn update_and_push_leaf_should_work() {
+	let mut smt = TestSparseMerkleTrie::<CheckMergeOrder>::empty();
+
+	(1..=9).for_each(|i| {
+		assert_eq!(smt.push_leaf(i), i - 1);
+		assert_eq!(
+			smt.nodes,
+			TestSparseMerkleTrie::<CheckMergeOrder>::new(1..=i).nodes
+		);
+	});
+
+	assert_eq!(smt.update_leaf(4, 50), Ok(()));
+	assert_eq!(
+		smt.nodes,
+		TestSparseMerkleTrie::<CheckMergeOrder>::new([1, 2, 3, 4, 50, 6, 7, 8, 9].into_iter())
+			.nodes
+	);
+	assert_eq!(smt.update_leaf(9, 10), Err(TreeError::IndexOutOfBounds(9)));
+
+	let mut single = TestSparseMerkleTrie::<CheckMergeOrder>::new(1..=1);
+
+	assert_eq!(single.update_leaf(0, 7), Ok(()));
+	assert_eq!(single.root(), 7);
+}
+
+#[test]
+fn contains_should_work() {
+	let smt = TestSparseMerkleTrie::<CheckMergeOrder>::new([5, 6, 7, 6, 8]);
+	let leaf_index = crate::keyed::LeafIndex::new(&smt);
+
+	assert_eq!(smt.leaves(), [5, 6, 7, 6, 8]);
+	assert!(smt.contains(2, &7));
+	assert!(!smt.contains(2, &6));
+	// The padding leaves.
+	assert!(!smt.contains(5, &0));
+	assert!(!smt.contains(8, &0));
+
+	[
+		(5, Some(0)),
+		(6, Some(1)),
+		(8, Some(4)),
+		(0, None),
+		(9, None),
+	]
+	.iter()
+	.for_each(|(leaf, index)| {
+		assert_eq!(smt.contains_hash(leaf), *index);
+		assert_eq!(leaf_index.get(leaf), *index);
+	});
+
+	assert!(TestSparseMerkleTrie::<CheckMergeOrder>::empty()
+		.leaves()
+		.is_empty());
+}
+
+#[test]
+fn max_depth_should_work() {
+	(0..=9).for_each(|n| {
+		let smt = TestSparseMerkleTrie::<CheckMergeOrder>::new_with_max_depth(1..=n, 4).unwrap();
+
+		assert_eq!(
+			smt.nodes,
+			TestSparseMerkleTrie::<CheckMergeOrder>::new(1..=n).nodes
+		);
+
+		if n != 0 {
+			let proof = smt.proof_of([n - 1]);
+
+			assert_eq!(proof.depth(), Some(smt.depth()));
+			assert!(TestSparseMerkleTrie::verify_with_max_depth(
+				proof.clone(),
+				4
+			));
+			assert_eq!(
+				TestSparseMerkleTrie::verify_with_max_depth(proof, 2),
+				smt.depth() <= 2
+			);
+		}
+	});
+
+	assert!(matches!(
+		TestSparseMerkleTrie::<CheckMergeOrder>::new_with_max_depth(1..=17, 4),
+		Err(TreeError::TooManyLeaves)
+	));
+	assert!(matches!(
+		TestSparseMerkleTrie::<CheckMergeOrder>::new_with_max_depth(1..=1, MAX_DEPTH + 1),
+		Err(TreeError::InvalidDepth)
+	));
+	assert_eq!(Proof::<u32, CheckMergeOrder>::default().depth(), None);
+
+	// Only the first leaf is in the depth.
+	let smt = TestSparseMerkleTrie::<CheckMergeOrder>::new(1..=2);
+	let mut proof = smt.proof_of([0]);
+
+	proof.leaves_with_index.push((1 << 31, 7));
+	proof.proof.push(7);
+
+	assert!(TestSparseMerkleTrie::verify(proof.clone()));
+	assert!(!TestSparseMerkleTrie::verify_with_max_depth(proof, 1));
+}
+
+#[test]
+fn normalize_should_work() {
+	let smt = TestSparseMerkleTrie::<CheckMergeOrder>::new(1..=9);
+	let expected = smt.proof_of([7, 3, 2, 0]);
+
+	[
+		[7, 3, 2, 0].as_ref(),
+		&[0, 2, 3, 7],
+		&[3, 7, 0, 3, 2, 7],
+		&[2, 2, 0, 0, 7, 3, 3],
+	]
+	.iter()
+	.for_each(|indices| {
+		let mut proof = smt.proof_of(*indices);
+
+		assert!(proof.normalize());
+		assert!(proof == expected);
+		assert!(TestSparseMerkleTrie::verify(proof));
+	});
+
+	// The verification consumes one sibling per duplicate leaf.
+	let expected = smt.proof_of([3]);
+	let mut duplicated = expected.clone();
+
+	duplicated
+		.leaves_with_index
+		.push(duplicated.leaves_with_index[0]);
+	duplicated.proof = expected.proof.iter().flat_map(|s| [*s, *s]).collect();
+
+	assert!(TestSparseMerkleTrie::verify(duplicated.clone()));
+
+	// The same index with different leaves isn't a duplicate.
+	let mut conflicting = duplicated.clone();
+
+	conflicting.leaves_with_index[1].1 += 1;
+
+	assert!(conflicting.normalize());
+	assert_eq!(conflicting.leaves_with_index.len(), 2);
+	assert_eq!(conflicting.proof.len(), duplicated.proof.len());
+
+	assert!(duplicated.normalize());
+	assert!(duplicated == expected);
+
+	let mut malformed = smt.proof_of([3, 0]);
+
+	malformed.proof.pop();
+
+	let untouched = malformed.clone();
+
+	assert!(!malformed.normalize());
+	assert!(malformed == untouched);
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn verify_all_should_work() {
+	let smt = TestSparseMerkleTrie::<CheckMergeOrder>::new(1..=100);
+	let mut proofs = (0..100).map(|i| smt.proof_of([i])).collect::<Vec<_>>();
+
+	proofs[7].root += 1;
+	proofs[42].leaves_with_index[0].1 += 1;
+
+	let verified = TestSparseMerkleTrie::<CheckMergeOrder>::verify_all(proofs);
+
+	assert_eq!(verified.len(), 100);
+	assert!(verified
+		.iter()
+		.enumerate()
+		.all(|(i, verified)| *verified == (i != 7 && i != 42)));
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn new_par_should_work() {
+	[0, 1, 2, 9, 1_000, 5_000].iter().for_each(|n| {
+		let leaves = || 1..=*n;
+
+		assert_eq!(
+			TestSparseMerkleTrie::<CheckMergeOrder>::new_par(leaves()).nodes,
+			TestSparseMerkleTrie::<CheckMergeOrder>::new(leaves()).nodes
+		);
+	});
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn rehash_par_should_work() {
+	[0, 1, 2, 9, 1_000, 5_000].iter().for_each(|n| {
+		let smt = TestSparseMerkleTrie::<DebugView>::new(1..=*n);
+
+		assert_eq!(
+			smt.rehash_par::<CheckMergeOrder>().nodes,
+			smt.rehash::<CheckMergeOrder>().nodes
+		);
+	});
+}
+
+#[cfg(feature = "threads")]
+#[test]
+fn new_with_threads_should_work() {
+	[0, 1, 2, 9, 1_000, 5_000].iter().for_each(|n| {
+		let leaves = || 1..=*n;
+
+		[1, 3, 8].iter().for_each(|workers| {
+			let smt = TestSparseMerkleTrie::<CheckMergeOrder>::new_with_threads(
+				leaves(),
+				core::num::NonZeroUsize::new(*workers).unwrap(),
+			);
+
+			assert_eq!(
+				smt.nodes,
+				TestSparseMerkleTrie::<CheckMergeOrder>::new(leaves()).nodes
+			);
+			assert_eq!(smt.non_empty_leaves_count(), *n);
+		});
+	});
+}
+
+#[test]
+fn proof_of_paged_should_work() {
+	let smt = TestSparseMerkleTrie::<CheckMergeOrder>::new(1..=100);
+	let indices = (0..100).filter(|i| i % 3 != 1).collect::<Vec<_>>();
+
+	for max_siblings_per_page in [0, 7, 10, 20, 50, 1_000] {
+		let pages = smt.proof_of_paged(&indices, max_siblings_per_page);
+		let mut proven = Vec::new();
+
+		for page in &pages {
+			assert!(page.proof.len() <= max_siblings_per_page.max(smt.depth() as usize));
+			assert!(TestSparseMerkleTrie::<CheckMergeOrder>::verify(
+				page.clone()
+			));
+
+			proven.extend(
+				page.leaves_with_index
+					.iter()
+					.map(|(i, _)| *i - smt.capacity()),
+			);
+		}
+
+		proven.sort_unstable();
+
+		assert_eq!(proven, indices);
+
+		if max_siblings_per_page == 0 {
+			assert_eq!(pages.len(), indices.len());
+		}
+		if max_siblings_per_page == 1_000 {
+			let mut descending = indices.clone();
+
+			descending.reverse();
+
+			assert_eq!(pages.len(), 1);
+			assert!(pages[0] == smt.proof_of(descending));
+		}
+	}
+
+	assert!(smt.proof_of_paged([100], 10).is_empty());
+	assert!(smt.proof_of_paged([0_u32; 0], 10).is_empty());
+}
+
+#[test]
+fn proof_of_paged_should_minimise_the_siblings() {
+	fn partitions(indices: &[u32]) -> Vec<Vec<Vec<u32>>> {
+		let Some((first, rest)) = indices.split_first() else {
+			return [Vec::new()].into();
+		};
+
+		partitions(rest)
+			.into_iter()
+			.flat_map(|partition| {
+				(0..=partition.len()).map(move |k| {
+					let mut partition = partition.clone();
+
+					if k == partition.len() {
+						partition.push([*first].into());
+					} else {
+						partition[k].push(*first);
+					}
+
+					partition
+				})
+			})
+			.collect()
+	}
+
+	let smt = TestSparseMerkleTrie::<DebugView>::new(1..=16);
+	let indices = [0, 1, 4, 6, 9, 10, 15];
+	let partitions = partitions(&indices);
+
+	for max_siblings_per_page in 0..=8 {
+		let minimum = partitions
+			.iter()
+			.filter_map(|partition| {
+				partition.iter().try_fold(0, |total, page| {
+					let count = smt.proof_of(page).proof.len();
+
+					(page.len() == 1 || count <= max_siblings_per_page).then_some(total + count)
+				})
+			})
+			.min()
+			.unwrap();
+		let pages = smt.proof_of_paged(indices, max_siblings_per_page);
+
+		assert_eq!(
+			pages.iter().map(|page| page.proof.len()).sum::<usize>(),
+			minimum
+		);
+	}
+
+	// Merging the adjacent subtrees greedily takes 26.
+	let smt = TestSparseMerkleTrie::<DebugView>::new(1..=64);
+	let pages = smt.proof_of_paged([8, 10, 22, 24, 25, 27, 28, 29, 30, 44, 49, 50, 55], 9);
+
+	assert_eq!(pages.iter().map(|page| page.proof.len()).sum::<usize>(), 25);
+}
+
+#[test]
+fn proofs_for_all_should_work() {
+	for n in 0..=9 {
+		let smt = TestSparseMerkleTrie::<CheckMergeOrder>::new(1..=n);
+		let mut count = 0;
+
+		for (i, proof) in smt.proofs_for_all() {
+			assert_eq!(i, count);
+			assert!(proof == smt.proof_of([i]));
+
+			count += 1;
+		}
+
+		assert_eq!(count, n);
+	}
+}
+
+#[test]
+fn nodes_accessors_should_work() {
+	let mut smt = TestSparseMerkleTrie::<CheckMergeOrder>::new(1..=5);
+	let nodes = smt.as_nodes().to_vec();
+
+	assert_eq!(nodes[8..13], [1, 2, 3, 4, 5]);
+
+	smt.as_nodes_mut_unchecked()[8] = 0;
+
+	assert_eq!(smt.root(), nodes[1]);
+	assert_eq!(
+		TestSparseMerkleTrie::<CheckMergeOrder>::from_nodes(smt.into_nodes(), 5).err(),
+		Some(TreeError::InconsistentNode(4))
+	);
+	assert!(TestSparseMerkleTrie::<CheckMergeOrder>::from_nodes(nodes, 5).is_ok());
+}
+
+#[test]
+fn into_iterator_should_work() {
+	let smt = TestSparseMerkleTrie::<CheckMergeOrder>::new(1..=5);
+	let proof = smt.proof_of([4, 2, 0]);
+
+	assert!(
+		TestSparseMerkleTrie::<CheckMergeOrder>::new([1, 2, 3, 4, 5]).as_nodes() == smt.as_nodes()
+	);
+	assert!(
+		TestSparseMerkleTrie::<CheckMergeOrder>::new(alloc::vec![1, 2, 3, 4, 5]).as_nodes()
+			== smt.as_nodes()
+	);
+	assert!(smt.proof_of([4, 2, 0].as_ref()) == proof);
+	assert!(smt.proof_of(alloc::vec![4, 2, 0]) == proof);
+	assert!(smt.proof_of((0..5).rev().step_by(2)) == proof);
+	// The set is in the ascending order.
+	assert!(
+		smt.proof_of(
+			alloc::collections::BTreeSet::from([4, 2, 0])
+				.into_iter()
+				.rev()
+		) == proof
+	);
+	assert!(smt.proof_of(alloc::collections::BTreeSet::from([0, 2, 4])) == smt.proof_of([0, 2, 4]));
+}
+
+#[test]
+fn rehash_should_work() {
+	struct OneEmpty;
+	impl Merge for OneEmpty {
+		type Item = u32;
+
+		fn empty() -> Self::Item {
+			1
+		}
+
+		fn merge(l: &Self::Item, r: &Self::Item) -> Self::Item {
+			*l + *r
+		}
+	}
+
+	[0, 1, 2, 5, 9].iter().for_each(|n| {
+		let smt = TestSparseMerkleTrie::<DebugView>::new(1..=*n);
+		let rehashed = smt.rehash::<CheckMergeOrder>();
+
+		assert_eq!(
+			rehashed.nodes,
+			TestSparseMerkleTrie::<CheckMergeOrder>::new(1..=*n).nodes
+		);
+		assert_eq!(rehashed.non_empty_leaves_count(), *n);
+		assert_eq!(
+			smt.rehash::<OneEmpty>().root(),
+			TestSparseMerkleTrie::<OneEmpty>::new(1..=*n).root()
+		);
+	});
+
+	// The fixed depth is kept.
+	let smt =
+		TestSparseMerkleTrie::<DebugView>::new_with_depth(1..=5, 5, &DefaultNodes::new(5)).unwrap();
+
+	assert_eq!(
+		smt.rehash::<OneEmpty>().nodes,
+		TestSparseMerkleTrie::<OneEmpty>::new_with_depth(1..=5, 5, &DefaultNodes::new(5))
+			.unwrap()
+			.nodes
+	);
+}
+
+#[test]
+fn proof_of_with_scratch_should_work() {
+	let mut scratch = ProofScratch::new();
+
+	// Reuse the scratch across the trees in different sizes.
+	[200, 5, 70, 1].iter().for_each(|n| {
+		let smt = TestSparseMerkleTrie::<CheckMergeOrder>::new(1..=*n);
+
+		[
+			[0].to_vec(),
+			[n - 1].to_vec(),
+			(0..*n).rev().step_by(3).collect(),
+			(0..*n).rev().collect(),
+		]
+		.iter()
+		.for_each(|indices| {
+			let proof = smt.proof_of_with_scratch(indices, &mut scratch);
+
+			assert!(proof == smt.proof_of(indices));
+			assert!(TestSparseMerkleTrie::verify(proof));
+		});
+	});
+}
+
+#[test]
+fn free_functions_should_work() {
+	let smt = TestSparseMerkleTrie::<CheckMergeOrder>::new(1..=5);
+	let proof = smt_proof::<CheckMergeOrder>(1..=5, [0, 4, 2]);
+
+	assert_eq!(smt_root::<CheckMergeOrder>(1..=5), smt.root());
+	assert!(proof == *smt.proof_of([4, 2, 0]).sort());
+	assert!(smt_verify(proof));
+	assert!(!smt_verify(smt_proof::<CheckMergeOrder>(1..=5, [5])));
+}
+
+#[test]
+fn new_should_not_trust_size_hint() {
+	let leaves = [1, 2, 3, 4, 5];
+	let smt = TestSparseMerkleTrie::<CheckMergeOrder>::new(leaves);
+	let filtered = || leaves.iter().copied().filter(|_| true);
+
+	assert_eq!(
+		TestSparseMerkleTrie::<CheckMergeOrder>::new(filtered()).nodes,
+		smt.nodes
+	);
+	assert_eq!(
+		TestSparseMerkleTrie::<CheckMergeOrder>::new(filtered()).non_empty_leaves_count(),
+		5
+	);
+	assert_eq!(
+		TestSparseMerkleTrie::<CheckMergeOrder>::new_with_max_depth(filtered(), 2).err(),
+		Some(TreeError::TooManyLeaves)
+	);
+	assert_eq!(
+		TestSparseMerkleTrie::<CheckMergeOrder>::new_with_depth(
+			filtered(),
+			2,
+			&DefaultNodes::new(2)
+		)
+		.err(),
+		Some(TreeError::TooManyLeaves)
+	);
 }
@@ -0,0 +1,42 @@
+// --- sparse-merkle-tree ---
+use crate::{hash::test::*, proof_cache::ProofCache, *};
+
+#[test]
+fn proof_cache_should_work() {
+	let mut cache = ProofCache::new(SparseMerkleTree::<_, CheckMergeOrder>::new(1..=9), 2);
+
+	assert!(cache.proof_of([0, 3]) == cache.tree().proof_of([3, 0]));
+	assert!(cache.proof_of([3, 0, 3]) == cache.tree().proof_of([3, 0]));
+	assert_eq!(cache.len(), 1);
+
+	cache.proof_of([1]);
+	cache.proof_of([3, 0]);
+	// Evict `[1]`, the least recently used one.
+	cache.proof_of([2]);
+
+	assert_eq!(cache.len(), 2);
+	assert!(cache.contains([0, 3]));
+	assert!(!cache.contains([1]));
+	assert!(cache.contains([2]));
+
+	// Not cached.
+	assert!(cache.proof_of([9]).leaves_with_index.is_empty());
+	assert_eq!(cache.len(), 2);
+
+	assert_eq!(cache.update_leaf(3, 40), Ok(()));
+	assert!(cache.is_empty());
+
+	let proof = cache.proof_of([3]);
+
+	assert_eq!(proof.leaves_with_index, [(19, 40)]);
+	assert!(SparseMerkleTree::verify(proof));
+
+	assert_eq!(cache.push_leaf(10), 9);
+	assert!(cache.is_empty());
+	assert!(SparseMerkleTree::verify(cache.proof_of([9, 3])));
+
+	let mut disabled = ProofCache::new(cache.into_tree(), 0);
+
+	assert!(SparseMerkleTree::verify(disabled.proof_of([9])));
+	assert!(disabled.is_empty());
+}
@@ -0,0 +1,25 @@
+// --- alloc ---
+use alloc::sync::Arc;
+// --- sparse-merkle-tree ---
+use crate::{hash::test::*, shared::Shared, *};
+
+#[test]
+fn shared_should_match_the_owned() {
+	let owned = SparseMerkleTree::<_, DebugBytes>::new((1..=9_u32).map(u32::to_be_bytes));
+	let shared = SparseMerkleTree::<_, Shared<DebugBytes>>::new(
+		(1..=9_u32).map(|i| Arc::new(i.to_be_bytes())),
+	);
+
+	assert_eq!(*shared.root(), owned.root());
+	assert_eq!(*shared.committed_root(), owned.committed_root());
+
+	let proof = shared.proof_of([8, 3, 0]);
+
+	// The siblings are shared with the tree.
+	assert!(Arc::ptr_eq(&proof.proof[0], &shared.as_nodes()[25]));
+	assert!(SparseMerkleTree::verify(proof.clone()));
+	assert!(proof.clone().into_owned() == owned.proof_of([8, 3, 0]));
+	assert!(SparseMerkleTree::verify(
+		owned.proof_of([8, 3, 0]).into_shared()
+	));
+}
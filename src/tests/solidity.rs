@@ -0,0 +1,36 @@
+// --- sparse-merkle-tree ---
+use crate::solidity;
+
+/// Reviewed by hand against [`SparseMerkleTree::verify`](crate::SparseMerkleTree::verify) and the
+/// [`Abi`](crate::codec::Abi) format. Never regenerate it from [`solidity::verifier`], any change
+/// of the codegen must fail here until the new output is reviewed and copied in.
+const GOLDEN: &str = include_str!("golden/EpochProofs.sol");
+
+#[test]
+fn verifier_should_match_the_golden_file() {
+	let source = solidity::verifier("EpochProofs");
+
+	if let Some((line, (actual, expected))) = source
+		.lines()
+		.zip(GOLDEN.lines())
+		.enumerate()
+		.find(|(_, (actual, expected))| actual != expected)
+	{
+		panic!(
+			"the verifier differs from the golden file at line {}:\n  actual:   {}\n  expected: {}",
+			line + 1,
+			actual,
+			expected
+		);
+	}
+
+	assert_eq!(source, GOLDEN);
+}
+
+#[test]
+fn library_name_should_be_the_only_variable() {
+	assert_eq!(
+		solidity::verifier("AirdropProofs"),
+		GOLDEN.replace("library EpochProofs {", "library AirdropProofs {")
+	);
+}
@@ -0,0 +1,46 @@
+// --- sparse-merkle-tree ---
+use crate::{
+	codec::{Abi, LittleEndian, ProofCodec, Scale},
+	hash::test::*,
+	*,
+};
+
+#[test]
+fn stats_should_work() {
+	let smt = SparseMerkleTree::<_, DebugBytes>::new((1..=5_u32).map(u32::to_be_bytes));
+	//         15
+	//     10       5
+	//   3    7   5   0
+	//  1 2  3 4 5 0 0 0
+	let proof = smt.proof_of([4]);
+	let stats = proof.stats();
+
+	assert_eq!(stats.siblings_count, 3);
+	assert_eq!(stats.depth, Some(3));
+	assert_eq!(stats.default_siblings_count, 2);
+	assert_eq!(stats.encoded_sizes.compact, 4 + 4 + (4 + 4) + 4 + 3 * 4 + 1);
+	assert_eq!(stats.encoded_sizes.scale, Scale::encode(&proof).len());
+	assert_eq!(stats.encoded_sizes.abi, Abi::encode(&proof).len());
+	assert_eq!(
+		stats.encoded_sizes.compressed,
+		Some(proof.compress().unwrap().encode::<LittleEndian>().len())
+	);
+	#[cfg(feature = "cbor")]
+	assert_eq!(
+		stats.encoded_sizes.cbor,
+		Some(crate::codec::Cbor::encode(&proof).len())
+	);
+	#[cfg(not(feature = "cbor"))]
+	assert_eq!(stats.encoded_sizes.cbor, None);
+
+	let stats = smt.proof_of([1, 0]).stats();
+
+	assert_eq!(stats.siblings_count, 2);
+	assert_eq!(stats.default_siblings_count, 0);
+
+	// No leaf.
+	let stats = Proof::<[u8; 4], DebugBytes>::default().stats();
+
+	assert_eq!(stats.depth, None);
+	assert_eq!(stats.encoded_sizes.compressed, None);
+}
@@ -0,0 +1,31 @@
+// --- crates.io ---
+use proptest::test_runner::TestRunner;
+// --- sparse-merkle-tree ---
+use crate::{hash::test::*, strategies::*, *};
+
+#[test]
+fn strategies_should_work() {
+	let mut runner = TestRunner::default();
+
+	runner
+		.run(
+			&valid_proof::<_, CheckMergeOrder, _>(1..1_000_u32, 20),
+			|case| {
+				assert_eq!(case.proof.validate_structure(), Ok(()));
+				assert!(SparseMerkleTree::verify(case.proof));
+
+				Ok(())
+			},
+		)
+		.unwrap();
+	runner
+		.run(
+			&invalid_proof::<_, CheckMergeOrder, _>(1..1_000_u32, 20),
+			|case| {
+				assert!(!SparseMerkleTree::verify(case.proof));
+
+				Ok(())
+			},
+		)
+		.unwrap();
+}
@@ -0,0 +1,63 @@
+// --- crates.io ---
+use rand_core::{Rng, SeedableRng};
+use rand_xorshift::XorShiftRng;
+// --- sparse-merkle-tree ---
+use crate::{hash::test::*, *};
+
+type TestProof = Proof<[u8; 4], DebugBytes>;
+
+#[test]
+fn corrupt_should_work() {
+	let smt = SparseMerkleTree::<_, DebugBytes>::new((1..=9_u32).map(u32::to_be_bytes));
+	let proof = smt.proof_of([7, 3, 0]);
+	let corrupted = |corrupt: &dyn Fn(&mut TestProof) -> bool| {
+		let mut corrupted = proof.clone();
+
+		assert!(corrupt(&mut corrupted));
+		assert!(corrupted != proof);
+		assert!(!SparseMerkleTree::verify(corrupted));
+	};
+
+	assert!(SparseMerkleTree::verify(proof.clone()));
+
+	(0..proof.proof.len() * 2).for_each(|k| {
+		corrupted(&|p| p.corrupt_flip_sibling(k, k * 7));
+		corrupted(&|p| p.corrupt_drop_sibling(k));
+	});
+	(0..3).for_each(|k| corrupted(&|p| p.corrupt_swap_leaves(k)));
+	corrupted(&|p| p.corrupt_root(smt.proof_of([8]).proof[0]));
+
+	// Nothing to corrupt.
+	let mut single = SparseMerkleTree::<_, DebugBytes>::new([[1; 4]]).proof_of([0]);
+	let untouched = single.clone();
+
+	assert!(!single.corrupt_flip_sibling(0, 0));
+	assert!(!single.corrupt_drop_sibling(0));
+	assert!(!single.corrupt_swap_leaves(0));
+	assert!(single == untouched);
+}
+
+#[test]
+fn random_should_work() {
+	let random = |seed, leaf_count| {
+		SparseMerkleTree::<[u8; 4], DebugBytes>::random(
+			&mut XorShiftRng::seed_from_u64(seed),
+			leaf_count,
+		)
+	};
+
+	assert_eq!(random(42, 13).as_nodes(), random(42, 13).as_nodes());
+	assert_ne!(random(42, 13).as_nodes(), random(43, 13).as_nodes());
+	assert_eq!(random(42, 13).non_empty_leaves_count(), 13);
+	// A prefix of the longer tree.
+	assert_eq!(random(42, 13).leaves(), &random(42, 16).leaves()[..13]);
+	assert!(random(42, 0).is_empty());
+
+	// Pin the stream, the other implementations replay it.
+	let mut rng = XorShiftRng::seed_from_u64(42);
+	let mut leaves = [[0; 4]; 3];
+
+	leaves.iter_mut().for_each(|leaf| rng.fill_bytes(leaf));
+
+	assert_eq!(random(42, 3).leaves(), leaves);
+}
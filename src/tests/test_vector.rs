@@ -0,0 +1,68 @@
+// --- sparse-merkle-tree ---
+use crate::{hash::test::*, test_vector::*, *};
+
+#[test]
+fn test_vector_should_work() {
+	//                15
+	//        0               15
+	//    0       0       10      5
+	//  0   0   0   0   3   7   5   0
+	// 0 0 0 0 0 0 0 0 1 2 3 4 5 0 0 0
+	let smt = SparseMerkleTree::<_, DebugBytes>::new((1..=5_u32).map(u32::to_be_bytes));
+	let json = smt.to_test_vector("debug-bytes", [4, 2]);
+	let test_vector = TestVector::<[u8; 4]>::from_json(&json).unwrap();
+
+	assert_eq!(
+		json,
+		r#"{
+  "hasher": "debug-bytes",
+  "indices": [
+    12,
+    10
+  ],
+  "leaves": [
+    "0x00000001",
+    "0x00000002",
+    "0x00000003",
+    "0x00000004",
+    "0x00000005"
+  ],
+  "non_empty_leaves_count": null,
+  "proven_leaves": [
+    "0x00000005",
+    "0x00000003"
+  ],
+  "root": "0x0000000f",
+  "siblings": [
+    "0x00000000",
+    "0x00000004",
+    "0x00000000",
+    "0x00000003"
+  ]
+}"#
+	);
+	assert_eq!(test_vector.to_json(), json);
+	assert!(test_vector.verify::<DebugBytes>());
+
+	let mut tampered = test_vector.clone();
+
+	tampered.leaves.as_mut().unwrap()[0] = [0, 0, 0, 2];
+
+	assert!(!tampered.verify::<DebugBytes>());
+
+	let proof = smt.committed_proof_of([3]);
+	let test_vector =
+		TestVector::<[u8; 4]>::from_json(&proof.to_test_vector("debug-bytes")).unwrap();
+
+	assert_eq!(test_vector.leaves, None);
+	assert_eq!(test_vector.non_empty_leaves_count, Some(5));
+	assert!(SparseMerkleTree::verify_committed(
+		test_vector.to_proof::<DebugBytes>().unwrap()
+	));
+
+	assert_eq!(TestVector::<[u8; 4]>::from_json("[]"), Err(Error::Json));
+	assert_eq!(
+		TestVector::<[u8; 4]>::from_json(&json.replace("0x0000000f", "0x0f")),
+		Err(Error::InvalidField("root"))
+	);
+}
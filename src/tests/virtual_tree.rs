@@ -0,0 +1,37 @@
+// --- sparse-merkle-tree ---
+use crate::{hash::test::*, virtual_tree::VirtualTree, *};
+
+#[test]
+fn virtual_tree_should_match_the_padded_tree() {
+	(0..=17).for_each(|n| {
+		let padded = SparseMerkleTree::<_, CheckMergeOrder>::new(1..=n);
+		let virtual_tree = VirtualTree::<_, CheckMergeOrder>::new(1..=n);
+
+		assert_eq!(virtual_tree.root(), padded.root());
+		assert_eq!(virtual_tree.depth(), padded.depth());
+		assert_eq!(virtual_tree.capacity(), padded.capacity());
+		assert_eq!(virtual_tree.non_empty_leaves_count(), n);
+
+		[
+			(0..n).rev().collect::<Vec<_>>(),
+			(0..n).rev().step_by(3).collect(),
+			(0..n).filter(|i| i % 4 == 1).rev().collect(),
+			[n.saturating_sub(1)].to_vec(),
+		]
+		.iter()
+		.filter(|indices| n != 0 && !indices.is_empty())
+		.for_each(|indices| {
+			let proof = virtual_tree.proof_of(indices);
+
+			assert!(proof == padded.proof_of(indices));
+			assert!(SparseMerkleTree::verify(proof));
+		});
+	});
+
+	// `2^4 + 1` leaves, the padded tree stores `2 * 2^5` nodes.
+	assert_eq!(
+		VirtualTree::<_, CheckMergeOrder>::new(1..=17).nodes_count(),
+		17 + 9 + 5 + 3 + 2 + 1
+	);
+	assert!(VirtualTree::<_, CheckMergeOrder>::new(1..=3).proof_of([3]) == Proof::default());
+}
@@ -0,0 +1,140 @@
+//! Pad the tree virtually, instead of storing the padding nodes.
+
+// --- core ---
+use core::marker::PhantomData;
+// --- alloc ---
+use alloc::vec::Vec;
+// --- sparse-merkle-tree ---
+use crate::*;
+
+/// A [`SparseMerkleTree`] which only stores the nodes covering at least one non-empty leaf.
+///
+/// The padding subtrees are taken from the [`DefaultNodes`] on demand, so a tree of `2^20 + 1`
+/// leaves stores about `2^21` nodes instead of `2^22`. The root and the proofs are the same as the
+/// ones of [`SparseMerkleTree::new`], they are verified by [`SparseMerkleTree::verify`] as usual.
+pub struct VirtualTree<H, M> {
+	/// From the leaves up to the root, each level is half of the previous one, rounded up.
+	levels: Vec<Vec<H>>,
+	default_nodes: DefaultNodes<H, M>,
+}
+impl<H, M> VirtualTree<H, M>
+where
+	H: Clone + MaybeDebug + PartialEq,
+	M: Merge<Item = H>,
+{
+	pub fn new<L>(leaves: L) -> Self
+	where
+		L: IntoIterator<Item = H>,
+	{
+		let leaves = leaves.into_iter().collect::<Vec<_>>();
+		let depth = non_empty_to_half_leaves_count(leaves.len() as _).trailing_zeros();
+		let default_nodes = DefaultNodes::new(depth);
+		let mut levels = Vec::with_capacity(depth as usize + 1);
+
+		levels.push(leaves);
+
+		for height in 0..depth {
+			let level = &levels[height as usize];
+			let default_node = default_nodes.get(height).expect("`height` < `depth`; qed");
+			let parents = level
+				.chunks(2)
+				.map(|pair| M::merge(&pair[0], pair.get(1).unwrap_or(default_node)))
+				.collect();
+
+			levels.push(parents);
+		}
+
+		Self {
+			levels,
+			default_nodes,
+		}
+	}
+
+	pub fn root(&self) -> H {
+		self.levels
+			.last()
+			.and_then(|level| level.first())
+			.cloned()
+			.unwrap_or_else(M::empty)
+	}
+
+	pub fn depth(&self) -> u32 {
+		self.levels.len() as u32 - 1
+	}
+
+	/// The leaves count of the equivalent [`SparseMerkleTree`], including the virtual ones.
+	pub fn capacity(&self) -> u32 {
+		if self.levels[0].is_empty() {
+			0
+		} else {
+			1 << self.depth()
+		}
+	}
+
+	pub fn non_empty_leaves_count(&self) -> u32 {
+		self.levels[0].len() as _
+	}
+
+	/// The number of the stored nodes.
+	pub fn nodes_count(&self) -> usize {
+		self.levels.iter().map(Vec::len).sum()
+	}
+
+	/// Same as [`SparseMerkleTree::proof_of`].
+	pub fn proof_of<I>(&self, indices: I) -> Proof<H, M>
+	where
+		I: IntoIterator,
+		I::Item: Borrow<u32>,
+	{
+		let indices = collect_indices(indices);
+
+		if indices.iter().any(|i| *i >= self.non_empty_leaves_count()) {
+			warn!("proof_of::Index out of bounds.");
+
+			return Proof::default();
+		}
+
+		let capacity = self.capacity();
+		let mut known = indices.to_vec();
+		let mut proof = Vec::new();
+
+		// Same order as `siblings_of`, level by level from the leaves, right to left.
+		for (height, level) in self.levels.iter().enumerate().take(self.depth() as _) {
+			known.sort_unstable_by(|a, b| b.cmp(a));
+			known.dedup();
+
+			let mut k = 0;
+
+			while k < known.len() {
+				let i = known[k];
+
+				if i & 1 == 1 && known.get(k + 1) == Some(&(i - 1)) {
+					k += 1;
+				} else {
+					proof.push(
+						level
+							.get((i ^ 1) as usize)
+							.or_else(|| self.default_nodes.get(height as _))
+							.expect("`height` < `depth`; qed")
+							.clone(),
+					);
+				}
+
+				k += 1;
+			}
+
+			known.iter_mut().for_each(|i| *i /= 2);
+		}
+
+		Proof {
+			root: self.root(),
+			leaves_with_index: indices
+				.iter()
+				.map(|i| (capacity + *i, self.levels[0][*i as usize].clone()))
+				.collect(),
+			proof,
+			non_empty_leaves_count: None,
+			_merge: PhantomData,
+		}
+	}
+}
@@ -0,0 +1,98 @@
+// --- std ---
+use std::{env, fs, path::PathBuf, process::Command};
+// --- crates.io ---
+use serde_json::Value;
+
+struct Leaves(PathBuf);
+impl Leaves {
+	fn new(name: &str, count: u32) -> Self {
+		let path = env::temp_dir().join(format!("tiny-smt-{}-{}.json", name, std::process::id()));
+		let leaves = (1..=count)
+			.map(|i| format!("0x{:064x}", i))
+			.collect::<Vec<_>>();
+
+		fs::write(&path, serde_json::to_string(&leaves).unwrap()).unwrap();
+
+		Self(path)
+	}
+}
+impl Drop for Leaves {
+	fn drop(&mut self) {
+		let _ = fs::remove_file(&self.0);
+	}
+}
+
+fn tiny_smt(leaves: &Leaves, args: &[&str]) -> (bool, Value) {
+	let output = Command::new(env!("CARGO_BIN_EXE_tiny-smt"))
+		.arg(&leaves.0)
+		.args(args)
+		.output()
+		.unwrap();
+
+	(
+		output.status.success(),
+		serde_json::from_slice(&output.stdout).unwrap_or_default(),
+	)
+}
+
+#[test]
+fn prove_then_verify_should_work() {
+	let leaves = Leaves::new("prove", 5);
+	let others = Leaves::new("others", 6);
+
+	for hasher in ["keccak256", "keccak256-sorted"] {
+		for format in ["compact", "compact-be", "scale", "abi"] {
+			let (success, output) = tiny_smt(
+				&leaves,
+				&["--hasher", hasher, "--format", format, "--prove", "0,4,2,4"],
+			);
+
+			assert!(success);
+			assert_eq!(output["leavesCount"], 5);
+			assert_eq!(output["indices"], serde_json::json!([4, 2, 0]));
+
+			let proof = output["proof"].as_str().unwrap();
+			let verify = ["--hasher", hasher, "--format", format, "--verify", proof];
+			let (success, output) = tiny_smt(&leaves, &verify);
+
+			assert!(success);
+			assert_eq!(output["valid"], true);
+
+			// Another tree.
+			let (success, output) = tiny_smt(&others, &verify);
+
+			assert!(!success);
+			assert_eq!(output["valid"], false);
+
+			// A tampered proof.
+			let mut tampered = proof.to_owned();
+			let last = if tampered.ends_with('0') { "1" } else { "0" };
+
+			tampered.replace_range(tampered.len() - 1.., last);
+
+			let (success, _) = tiny_smt(
+				&leaves,
+				&[
+					"--hasher", hasher, "--format", format, "--verify", &tampered,
+				],
+			);
+
+			assert!(!success);
+		}
+	}
+
+	// The other hasher.
+	let (_, output) = tiny_smt(&leaves, &["--prove", "1"]);
+	let proof = output["proof"].as_str().unwrap();
+	let (success, _) = tiny_smt(
+		&leaves,
+		&["--hasher", "keccak256-sorted", "--verify", proof],
+	);
+
+	assert!(!success);
+
+	// Out of bounds.
+	let (success, _) = tiny_smt(&leaves, &["--prove", "5"]);
+
+	assert!(!success);
+}